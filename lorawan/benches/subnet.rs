@@ -0,0 +1,159 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use lorawan::subnet::{DevAddr, NetId, NetIdList, NetIdSet, SubnetAddr};
+
+const LIST_SIZES: [u32; 5] = [1, 8, 32, 50, 64];
+// The size of the lists NetIdSet lookups are compared on
+const SET_SIZE: u32 = 64;
+
+// Roughly 95% of traffic seen by a gateway is for devices outside the Helium
+// NetIDs, so only one in every 20 devaddrs is local.
+const LOCAL_EVERY: usize = 20;
+const DEVADDR_COUNT: usize = 1000;
+
+// Class 3 NetIDs have an 11 bit id, enough for distinct NetIDs in every list
+fn netid_list(size: u32) -> Vec<NetId> {
+    (0..size).map(|id| NetId::from(0x600000 | id)).collect()
+}
+
+fn devaddrs(netids: &[NetId]) -> Vec<DevAddr> {
+    let foreign = NetId::from(0x600000 | 0x7FF);
+    (0..DEVADDR_COUNT)
+        .map(|i| {
+            if i % LOCAL_EVERY == 0 {
+                let netid = netids[(i / LOCAL_EVERY) % netids.len()];
+                netid.to_devaddr(i as u32 % netid.size())
+            } else {
+                foreign.to_devaddr(i as u32)
+            }
+        })
+        .collect()
+}
+
+fn subnet_addrs(netids: &[NetId]) -> Vec<SubnetAddr> {
+    devaddrs(netids)
+        .iter()
+        .filter_map(|devaddr| SubnetAddr::from_devaddr(devaddr, netids))
+        .collect()
+}
+
+fn bench_from_devaddr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SubnetAddr::from_devaddr");
+    for size in LIST_SIZES {
+        let netids = netid_list(size);
+        let devaddrs = devaddrs(&netids);
+        group.bench_with_input(BenchmarkId::new("slice", size), &devaddrs, |b, devaddrs| {
+            b.iter(|| {
+                for devaddr in devaddrs {
+                    black_box(SubnetAddr::from_devaddr(devaddr, &netids));
+                }
+            })
+        });
+        let netid_list = NetIdList::try_from(netids.clone()).expect("netid list");
+        group.bench_with_input(
+            BenchmarkId::new("NetIdList", size),
+            &devaddrs,
+            |b, devaddrs| {
+                b.iter(|| {
+                    for devaddr in devaddrs {
+                        black_box(netid_list.subnet_from_devaddr(devaddr));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_from_subnet(c: &mut Criterion) {
+    let mut group = c.benchmark_group("DevAddr::from_subnet");
+    for size in LIST_SIZES {
+        let netids = netid_list(size);
+        let subnet_addrs = subnet_addrs(&netids);
+        group.bench_with_input(
+            BenchmarkId::new("slice", size),
+            &subnet_addrs,
+            |b, addrs| {
+                b.iter(|| {
+                    for subnet_addr in addrs {
+                        black_box(DevAddr::from_subnet(subnet_addr, &netids));
+                    }
+                })
+            },
+        );
+        let netid_list = NetIdList::try_from(netids.clone()).expect("netid list");
+        group.bench_with_input(
+            BenchmarkId::new("NetIdList", size),
+            &subnet_addrs,
+            |b, addrs| {
+                b.iter(|| {
+                    for subnet_addr in addrs {
+                        black_box(netid_list.devaddr_from_subnet(subnet_addr));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_addr_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NetId::addr_range");
+    for size in LIST_SIZES {
+        let netids = netid_list(size);
+        group.bench_with_input(BenchmarkId::new("slice", size), &netids, |b, netids| {
+            b.iter(|| {
+                for netid in netids {
+                    black_box(netid.addr_range(netids));
+                }
+            })
+        });
+        let netid_list = NetIdList::try_from(netids.clone()).expect("netid list");
+        group.bench_with_input(BenchmarkId::new("NetIdList", size), &netids, |b, netids| {
+            b.iter(|| {
+                for netid in netids {
+                    black_box(netid_list.addr_range(netid));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_is_local(c: &mut Criterion) {
+    let mut group = c.benchmark_group("NetId::is_local");
+    let netids = netid_list(SET_SIZE);
+    let candidates: Vec<NetId> = devaddrs(&netids).iter().map(DevAddr::net_id).collect();
+    group.bench_with_input(
+        BenchmarkId::new("slice", SET_SIZE),
+        &candidates,
+        |b, candidates| {
+            b.iter(|| {
+                for netid in candidates {
+                    black_box(netid.is_local(&netids));
+                }
+            })
+        },
+    );
+    let netid_set = NetIdSet::from(&netids[..]);
+    group.bench_with_input(
+        BenchmarkId::new("NetIdSet", SET_SIZE),
+        &candidates,
+        |b, candidates| {
+            b.iter(|| {
+                for netid in candidates {
+                    black_box(netid_set.is_local(netid));
+                }
+            })
+        },
+    );
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_from_devaddr,
+    bench_from_subnet,
+    bench_addr_range,
+    bench_is_local
+);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Parses arbitrary devaddrs. Run from the lorawan directory with
+//! `cargo fuzz run devaddr`.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use lorawan::subnet::{DevAddr, NetId, SubnetAddr};
+
+const NETID_LIST: [u32; 3] = [0xE00001, 0xC00035, 0x60002D];
+
+fuzz_target!(|raw: u32| {
+    let devaddr = DevAddr::from(raw);
+    let netid = devaddr.net_id();
+    assert!(netid.class() < 8);
+
+    // Devaddrs of a listed NetID translate to a subnet address and back.
+    // Adding the devaddr's own NetID makes sure that path is taken.
+    let mut netid_list: Vec<NetId> = NETID_LIST.into_iter().map(NetId::from).collect();
+    netid_list.push(netid);
+    match devaddr.try_net_id() {
+        Ok(_) => {
+            let subnet =
+                SubnetAddr::from_devaddr(&devaddr, &netid_list).expect("devaddr of a listed netid");
+            assert_eq!(Some(devaddr), DevAddr::from_subnet(&subnet, &netid_list));
+        }
+        Err(_) => assert_eq!(None, SubnetAddr::from_devaddr(&devaddr, &netid_list)),
+    }
+});
@@ -1,10 +1,27 @@
-use std::{error::Error, fmt, io};
+use crate::subnet::{NetId, SubnetAddr};
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{error::Error, io};
 
 #[derive(Debug)]
 pub enum LoraWanError {
     InvalidPacketType(u8),
     InvalidFPortForFopts,
     InvalidPacketSize(super::MType, usize),
+    InvalidDevAddr(String),
+    InvalidDevAddrSize(usize),
+    InvalidEui(String),
+    NetIdNotLocal(NetId),
+    RetiredNetId,
+    SubnetOutOfRange(SubnetAddr),
+    SubnetOverflow(NetId),
+    InvalidNetClass(u8),
+    InvalidNetId(String),
+    NetIdOverflow(u8, u32),
+    DuplicateNetId(NetId),
+    OverlappingNetIds(NetId, NetId),
+    #[cfg(feature = "std")]
     Io(io::Error),
 }
 
@@ -16,13 +33,45 @@ impl fmt::Display for LoraWanError {
             LoraWanError::InvalidPacketSize(mtype, s) => {
                 write!(f, "Invalid packet size {} for type {:?}", s, mtype)
             }
+            LoraWanError::InvalidDevAddr(s) => write!(f, "Invalid devaddr: {:?}", s),
+            LoraWanError::InvalidDevAddrSize(s) => write!(f, "Invalid devaddr size {}", s),
+            LoraWanError::InvalidEui(s) => write!(f, "Invalid EUI: {:?}", s),
+            LoraWanError::NetIdNotLocal(netid) => {
+                write!(f, "NetID {:#08x} not in NetID list", u32::from(*netid))
+            }
+            LoraWanError::RetiredNetId => write!(f, "Legacy devaddr of the retired Helium NetID"),
+            LoraWanError::SubnetOutOfRange(subnet) => {
+                write!(f, "Subnet address {} out of range", u32::from(*subnet))
+            }
+            LoraWanError::SubnetOverflow(netid) => write!(
+                f,
+                "NetID {:#08x} overflows the subnet address space",
+                u32::from(*netid)
+            ),
+            LoraWanError::InvalidNetClass(class) => write!(f, "Invalid NetID class: {}", class),
+            LoraWanError::InvalidNetId(s) => write!(f, "Invalid NetID: {:?}", s),
+            LoraWanError::NetIdOverflow(class, id) => {
+                write!(f, "Id {:#x} too large for NetID class {}", id, class)
+            }
+            LoraWanError::DuplicateNetId(netid) => {
+                write!(f, "Duplicate NetID {:#08x}", u32::from(*netid))
+            }
+            LoraWanError::OverlappingNetIds(a, b) => write!(
+                f,
+                "NetIDs {:#08x} and {:#08x} overlap",
+                u32::from(*a),
+                u32::from(*b)
+            ),
+            #[cfg(feature = "std")]
             LoraWanError::Io(err) => err.fmt(f),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl Error for LoraWanError {}
 
+#[cfg(feature = "std")]
 impl From<io::Error> for LoraWanError {
     fn from(err: io::Error) -> Self {
         LoraWanError::Io(err)
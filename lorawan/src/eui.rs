@@ -0,0 +1,95 @@
+use crate::LoraWanError;
+use alloc::string::ToString;
+use core::{fmt, str::FromStr};
+
+macro_rules! impl_eui {
+    ($type:ident) => {
+        impl From<u64> for $type {
+            fn from(v: u64) -> Self {
+                Self(v)
+            }
+        }
+
+        impl From<$type> for u64 {
+            fn from(v: $type) -> Self {
+                v.0
+            }
+        }
+
+        /// Interprets the bytes as big-endian
+        impl From<[u8; 8]> for $type {
+            fn from(v: [u8; 8]) -> Self {
+                Self(u64::from_be_bytes(v))
+            }
+        }
+
+        impl $type {
+            pub fn to_be_bytes(&self) -> [u8; 8] {
+                self.0.to_be_bytes()
+            }
+        }
+
+        impl fmt::Display for $type {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{:016X}", self.0)
+            }
+        }
+
+        /// Parses exactly 16 hex digits
+        impl FromStr for $type {
+            type Err = LoraWanError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                if s.len() != 16 || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(LoraWanError::InvalidEui(s.to_string()));
+                }
+                u64::from_str_radix(s, 16)
+                    .map(Self)
+                    .map_err(|_| LoraWanError::InvalidEui(s.to_string()))
+            }
+        }
+    };
+}
+
+/// A 64-bit LoRaWAN device EUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DevEui(u64);
+
+/// A 64-bit LoRaWAN join EUI, called the AppEUI before LoRaWAN 1.1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JoinEui(u64);
+
+impl_eui!(DevEui);
+impl_eui!(JoinEui);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_eui() {
+        let bytes = [0x70, 0xB3, 0xD5, 0x7E, 0xD0, 0x00, 0x12, 0x34];
+        let eui = DevEui::from(bytes);
+        assert_eq!(DevEui::from(0x70B3D57ED0001234), eui);
+        assert_eq!(bytes, eui.to_be_bytes());
+        assert_eq!("70B3D57ED0001234", eui.to_string());
+        assert_eq!(eui, "70B3D57ED0001234".parse().unwrap());
+        assert_eq!(eui, "70b3d57ed0001234".parse().unwrap());
+        assert_eq!(eui, eui.to_string().parse().unwrap());
+        assert_eq!("0000000000000001", DevEui::from(1).to_string());
+
+        assert!("70B3D57ED000123".parse::<DevEui>().is_err());
+        assert!("70B3D57ED000123456".parse::<DevEui>().is_err());
+        assert!("+0B3D57ED0001234".parse::<DevEui>().is_err());
+        assert!("70B3D57ED00012XY".parse::<DevEui>().is_err());
+    }
+
+    #[test]
+    fn test_join_eui() {
+        let bytes = [0, 0, 0, 0, 0, 0, 0, 0x2A];
+        let eui = JoinEui::from(bytes);
+        assert_eq!(42, u64::from(eui));
+        assert_eq!(bytes, eui.to_be_bytes());
+        assert_eq!(eui, eui.to_string().parse().unwrap());
+    }
+}
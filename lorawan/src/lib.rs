@@ -1,9 +1,18 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
 use bitfield::bitfield;
+#[cfg(feature = "std")]
 use byteorder::{LittleEndian, ReadBytesExt};
-use std::{convert::From, fmt, io, result};
+use core::{convert::From, fmt, result};
+#[cfg(feature = "std")]
+use std::io;
 
 pub mod error;
 pub use error::LoraWanError;
+pub mod eui;
 pub mod subnet;
 
 #[derive(Debug)]
@@ -46,6 +55,7 @@ bitfield! {
 }
 
 impl MHDR {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         Ok(Self(reader.read_u8()?))
     }
@@ -58,12 +68,17 @@ pub struct PHYPayload {
     pub mic: [u8; 4],
 }
 
+#[cfg(feature = "std")]
 const JOIN_REQUEST_LEN: usize = 23;
+#[cfg(feature = "std")]
 const JOIN_ACCEPT_LEN: usize = 17;
+#[cfg(feature = "std")]
 const JOIN_ACCEPT_WITH_CFLIST_LEN: usize = 33;
+#[cfg(feature = "std")]
 const DATA_MIN_LEN: usize = 12;
 
 impl PHYPayload {
+    #[cfg(feature = "std")]
     pub fn read(direction: Direction, reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let mhdr = MHDR::read(reader)?;
         let packet_type = mhdr.mtype();
@@ -113,6 +128,7 @@ pub enum PHYPayloadFrame {
 }
 
 impl PHYPayloadFrame {
+    #[cfg(feature = "std")]
     pub fn read(
         direction: Direction,
         packet_type: MType,
@@ -153,6 +169,7 @@ impl fmt::Debug for Fhdr {
 }
 
 impl Fhdr {
+    #[cfg(feature = "std")]
     pub fn read(direction: Direction, reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let dev_addr = reader.read_u32::<LittleEndian>()?;
         let fctrl = FCtrl::read(direction, reader)?;
@@ -180,6 +197,7 @@ bitfield! {
 }
 
 impl FCtrlUplink {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         Ok(Self(reader.read_u8()?))
     }
@@ -196,6 +214,7 @@ bitfield! {
 }
 
 impl FCtrlDownlink {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         Ok(Self(reader.read_u8()?))
     }
@@ -215,6 +234,7 @@ impl FCtrl {
         }
     }
 
+    #[cfg(feature = "std")]
     pub fn read(direction: Direction, reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let res = match direction {
             Direction::Uplink => Self::Uplink(FCtrlUplink::read(reader)?),
@@ -232,6 +252,7 @@ pub struct MACPayload {
 }
 
 impl MACPayload {
+    #[cfg(feature = "std")]
     pub fn read(
         payload_type: MType,
         direction: Direction,
@@ -272,6 +293,7 @@ pub enum FRMPayload {
 }
 
 impl FRMPayload {
+    #[cfg(feature = "std")]
     pub fn read(payload_type: MType, reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let res = match payload_type {
             MType::UnconfirmedUp => Self::UnconfirmedUp(Payload::read(reader)?),
@@ -289,6 +311,7 @@ impl FRMPayload {
 pub struct Payload(Vec<u8>);
 
 impl Payload {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let mut data = vec![];
         reader.read_to_end(&mut data)?;
@@ -314,6 +337,7 @@ impl fmt::Debug for JoinRequest {
 }
 
 impl JoinRequest {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let mut res = Self {
             app_eui: reader.read_u64::<LittleEndian>()?,
@@ -336,6 +360,7 @@ pub struct JoinAccept {
 }
 
 impl JoinAccept {
+    #[cfg(feature = "std")]
     pub fn read(reader: &mut dyn io::Read) -> Result<Self, LoraWanError> {
         let mut app_nonce = [0u8; 3];
         let mut net_id = [0u8; 3];
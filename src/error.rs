@@ -20,6 +20,8 @@ pub enum Error {
     Decode(#[from] DecodeError),
     #[error("service error {0}")]
     Service(#[from] ServiceError),
+    #[error("gateway error {0}")]
+    Gateway(#[from] GatewayError),
     #[error("state channel error")]
     StateChannel(#[from] Box<StateChannelError>),
     #[error("semtech udp error")]
@@ -56,6 +58,10 @@ pub enum DecodeError {
     InvalidCrc,
     #[error("unexpected transaction in envelope")]
     InvalidEnvelope,
+    #[error("uri without scheme or host: {0}")]
+    IncompleteUri(String),
+    #[error("unknown region {0}")]
+    UnknownRegion(i32),
 }
 
 #[derive(Error, Debug)]
@@ -70,10 +76,28 @@ pub enum ServiceError {
     Channel,
     #[error("no service")]
     NoService,
-    #[error("block age {block_age}s > {max_age}s")]
-    Check { block_age: u64, max_age: u64 },
+    #[error("stale validator, block age {block_age}s > {max_age}s")]
+    StaleValidator { block_age: u64, max_age: u64 },
     #[error("Unable to connect to local server. Check that `helium_gateway` is running.")]
     LocalClientConnect(helium_proto::services::Error),
+    #[error("gave up after {attempts} consecutive stream failures")]
+    Reconnect { attempts: u32 },
+    #[error("service shutting down")]
+    Shutdown,
+}
+
+#[derive(Error, Debug)]
+pub enum GatewayError {
+    #[error("unexpected gateway message, expected {expected}, got {got}")]
+    UnexpectedMessage { expected: &'static str, got: String },
+    #[error("empty {rpc} response")]
+    EmptyResponse { rpc: &'static str },
+    #[error("height went backwards from {last} to {height}")]
+    HeightRegression { last: u64, height: u64 },
+    #[error("pubkey mismatch for {uri}, expected {expected}")]
+    PubkeyMismatch { uri: String, expected: String },
+    #[error("missing config keys: {}", .keys.join(", "))]
+    MissingKeys { keys: Vec<String> },
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -162,6 +186,41 @@ impl DecodeError {
     pub fn prost_decode(msg: &'static str) -> Error {
         Error::Decode(prost::DecodeError::new(msg).into())
     }
+
+    pub fn incomplete_uri(uri: &http::Uri) -> Error {
+        Error::Decode(DecodeError::IncompleteUri(uri.to_string()))
+    }
+
+    pub fn unknown_region(region: i32) -> Error {
+        Error::Decode(DecodeError::UnknownRegion(region))
+    }
+}
+
+// Gateway Errors
+impl GatewayError {
+    pub fn unexpected_message<T: std::fmt::Debug>(expected: &'static str, got: T) -> Error {
+        let got = format!("{got:?}");
+        Error::Gateway(Self::UnexpectedMessage { expected, got })
+    }
+
+    pub fn empty_response(rpc: &'static str) -> Error {
+        Error::Gateway(Self::EmptyResponse { rpc })
+    }
+
+    pub fn height_regression(last: u64, height: u64) -> Error {
+        Error::Gateway(Self::HeightRegression { last, height })
+    }
+
+    pub fn missing_keys(keys: Vec<String>) -> Error {
+        Error::Gateway(Self::MissingKeys { keys })
+    }
+
+    pub fn pubkey_mismatch(uri: &crate::KeyedUri, expected: &crate::PublicKey) -> Error {
+        Error::Gateway(Self::PubkeyMismatch {
+            uri: uri.to_string(),
+            expected: expected.to_string(),
+        })
+    }
 }
 
 // State Channel Errors
@@ -233,7 +292,52 @@ impl Error {
         Error::Service(ServiceError::LocalClientConnect(e))
     }
 
-    pub fn gateway_service_check(block_age: u64, max_age: u64) -> Error {
-        Error::Service(ServiceError::Check { block_age, max_age })
+    pub fn stale_validator(block_age: u64, max_age: u64) -> Error {
+        Error::Service(ServiceError::StaleValidator { block_age, max_age })
+    }
+
+    pub fn reconnect(attempts: u32) -> Error {
+        Error::Service(ServiceError::Reconnect { attempts })
+    }
+
+    pub fn shutdown() -> Error {
+        Error::Service(ServiceError::Shutdown)
+    }
+
+    /// The gRPC status code of a failed request, for errors the validator or
+    /// the transport reported as a status
+    pub fn grpc_code(&self) -> Option<tonic::Code> {
+        self.grpc_status().map(tonic::Status::code)
+    }
+
+    /// The message of the gRPC status of a failed request. See `grpc_code`.
+    pub fn grpc_message(&self) -> Option<&str> {
+        self.grpc_status().map(tonic::Status::message)
+    }
+
+    fn grpc_status(&self) -> Option<&tonic::Status> {
+        match self {
+            Error::Service(ServiceError::Rpc(status)) => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a transport or server availability error that may
+    /// succeed when retried, possibly over a new connection.
+    pub fn is_transient(&self) -> bool {
+        use tonic::Code;
+        match self {
+            Error::Service(ServiceError::Service(_)) | Error::Service(ServiceError::Stream) => true,
+            Error::Service(ServiceError::Rpc(status)) => matches!(
+                status.code(),
+                Code::Unavailable
+                    | Code::Unknown
+                    | Code::Cancelled
+                    | Code::Aborted
+                    | Code::DeadlineExceeded
+                    | Code::Internal
+            ),
+            _ => false,
+        }
     }
 }
@@ -1,6 +1,10 @@
-use crate::{PublicKey, Result};
+use crate::{
+    error::{DecodeError, GatewayError},
+    PublicKey, Result,
+};
 use http::Uri;
 use serde::{de, Deserialize, Deserializer};
+use slog::warn;
 use std::{fmt, str::FromStr, sync::Arc};
 
 /// A URI that has an associated public key
@@ -27,6 +31,61 @@ impl fmt::Debug for KeyedUri {
     }
 }
 
+/// Renders the uri and the start of the pubkey, enough to tell validators
+/// apart in logs
+impl fmt::Display for KeyedUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pubkey = self.pubkey.to_string();
+        let prefix = pubkey.get(..PUBKEY_DISPLAY_LEN).unwrap_or(&pubkey);
+        write!(f, "{} ({}..)", self.uri, prefix)
+    }
+}
+
+const PUBKEY_DISPLAY_LEN: usize = 8;
+
+impl KeyedUri {
+    /// Check that this uri's pubkey is the expected one, for example the
+    /// pubkey a validator was configured with
+    pub fn verify_matches(&self, expected: &PublicKey) -> Result {
+        if self.pubkey.as_ref() == expected {
+            Ok(())
+        } else {
+            Err(GatewayError::pubkey_mismatch(self, expected))
+        }
+    }
+
+    /// Like `try_from` but also rejects uris without a scheme or host, which
+    /// parse as valid uris but can't be connected to
+    pub fn try_from_checked(v: helium_proto::services::local::KeyedUri) -> Result<Self> {
+        let keyed_uri = Self::try_from(v)?;
+        if keyed_uri.uri.scheme().is_none() || keyed_uri.uri.host().is_none() {
+            return Err(DecodeError::incomplete_uri(&keyed_uri.uri));
+        }
+        Ok(keyed_uri)
+    }
+
+    /// Convert a list of uris handed out by a validator, skipping entries
+    /// that fail `try_from_checked`, like those with a malformed public key,
+    /// with a warning naming the entry. Entries repeating the uri of an
+    /// earlier entry are dropped, so one validator can't crowd out others
+    /// by being listed many times.
+    pub fn from_list(entries: Vec<helium_proto::services::local::KeyedUri>) -> Vec<Self> {
+        let logger = slog_scope::logger();
+        let mut keyed_uris: Vec<Self> = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.into_iter().enumerate() {
+            let uri = entry.uri.clone();
+            match Self::try_from_checked(entry) {
+                Ok(keyed_uri) if keyed_uris.iter().any(|k| k.uri == keyed_uri.uri) => {
+                    warn!(logger, "skipping duplicate validator entry {index}: {uri}")
+                }
+                Ok(keyed_uri) => keyed_uris.push(keyed_uri),
+                Err(err) => warn!(logger, "skipping validator entry {index}: {uri}: {err:?}"),
+            }
+        }
+        keyed_uris
+    }
+}
+
 fn deserialize_pubkey<'de, D>(d: D) -> std::result::Result<Arc<PublicKey>, D::Error>
 where
     D: Deserializer<'de>,
@@ -69,3 +128,74 @@ impl From<KeyedUri> for helium_proto::services::local::KeyedUri {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUBKEY: &str = "11tk4zzbyfMPYYHYda255ACoqfYFVdrUSoCWrCYfn8BoyuYrERK";
+
+    fn keyed_uri(uri: &'static str) -> KeyedUri {
+        KeyedUri {
+            uri: Uri::from_static(uri),
+            pubkey: Arc::new(PUBKEY.parse().expect("pubkey")),
+        }
+    }
+
+    #[test]
+    fn display() {
+        let keyed_uri = keyed_uri("http://127.0.0.1:8080");
+        assert_eq!("http://127.0.0.1:8080/ (11tk4zzb..)", keyed_uri.to_string());
+    }
+
+    #[test]
+    fn verify_matches() {
+        let keyed_uri = keyed_uri("http://127.0.0.1:8080");
+        keyed_uri
+            .verify_matches(&PUBKEY.parse().expect("pubkey"))
+            .expect("matching pubkey");
+        let other: PublicKey = "112qB3YaH5bZkCnKA5uRH7tBtGNv2Y5B4smv1jsmvGUzgKT71QpE"
+            .parse()
+            .expect("pubkey");
+        assert!(matches!(
+            keyed_uri.verify_matches(&other),
+            Err(crate::Error::Gateway(GatewayError::PubkeyMismatch { .. }))
+        ));
+    }
+
+    #[test]
+    fn try_from_checked() {
+        let proto = |uri: &str| helium_proto::services::local::KeyedUri {
+            uri: uri.to_string(),
+            address: keyed_uri("http://127.0.0.1:8080").pubkey.to_vec(),
+        };
+        KeyedUri::try_from_checked(proto("http://127.0.0.1:8080")).expect("complete uri");
+        assert!(KeyedUri::try_from(proto("127.0.0.1")).is_ok());
+        assert!(matches!(
+            KeyedUri::try_from_checked(proto("127.0.0.1")),
+            Err(crate::Error::Decode(DecodeError::IncompleteUri(_)))
+        ));
+    }
+
+    #[test]
+    fn from_list() {
+        let pubkey = keyed_uri("http://127.0.0.1:8080").pubkey.to_vec();
+        let proto = |uri: &str, address: &[u8]| helium_proto::services::local::KeyedUri {
+            uri: uri.to_string(),
+            address: address.to_vec(),
+        };
+        let keyed_uris = KeyedUri::from_list(vec![
+            proto("http://127.0.0.1:8080", &pubkey),
+            proto("http://127.0.0.1:8081", &pubkey[1..]),
+            proto("http://127.0.0.1:8080", &pubkey),
+            proto("http://127.0.0.1:8082", &pubkey),
+        ]);
+        assert_eq!(
+            vec![
+                keyed_uri("http://127.0.0.1:8080"),
+                keyed_uri("http://127.0.0.1:8082")
+            ],
+            keyed_uris
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{Error, Result};
-use helium_proto::Region as ProtoRegion;
+use crate::{error::DecodeError, Error, Result};
+use helium_proto::{
+    BlockchainRegionParamV1, GatewayRegionParamsStreamedRespV1, Region as ProtoRegion,
+    RegionSpreading,
+};
 use serde::{de, Deserialize, Deserializer};
 use std::fmt;
 
@@ -73,6 +76,402 @@ impl Region {
     pub fn from_i32(v: i32) -> Result<Self> {
         ProtoRegion::from_i32(v)
             .map(Self)
-            .ok_or_else(|| Error::custom(format!("unsupported region {v}")))
+            .ok_or_else(|| DecodeError::unknown_region(v))
+    }
+
+    /// The default channels of this region per the LoRaWAN Regional
+    /// Parameters, for forwarders that configure their radio before, or
+    /// without, receiving region params from a validator.
+    ///
+    /// US915 and AU915 plans list all 72 uplink channels, not just the sub
+    /// band a gateway listens on. Max packet sizes are the largest MAC
+    /// payloads without dwell time limits.
+    pub fn default_frequency_plan(&self) -> FrequencyPlan {
+        const EU_RATES: [(u8, u32); 6] =
+            [(12, 59), (11, 59), (10, 59), (9, 123), (8, 230), (7, 230)];
+        const US_DOWN_RATES: [(u8, u32); 6] =
+            [(12, 61), (11, 137), (10, 250), (9, 250), (8, 250), (7, 250)];
+        // AS923 variants shift the AS923_1 channels by a fixed offset
+        let as923 = |offset: u64| {
+            let uplink = channels(923_200_000 - offset, 200_000, 2, 125_000, 160, &EU_RATES);
+            FrequencyPlan {
+                downlink: uplink.clone(),
+                rx2: channel(923_200_000 - offset, 125_000, 160, &EU_RATES[2..3]),
+                uplink,
+            }
+        };
+        // Plans whose downlinks answer on the uplink channel
+        let symmetric = |uplink: Vec<RegionChannel>, rx2: RegionChannel| FrequencyPlan {
+            downlink: uplink.clone(),
+            uplink,
+            rx2,
+        };
+        match self.0 {
+            ProtoRegion::Us915 => FrequencyPlan {
+                uplink: [
+                    channels(
+                        902_300_000,
+                        200_000,
+                        64,
+                        125_000,
+                        300,
+                        &[(10, 19), (9, 61), (8, 133), (7, 250)],
+                    ),
+                    channels(903_000_000, 1_600_000, 8, 500_000, 300, &[(8, 250)]),
+                ]
+                .concat(),
+                downlink: channels(923_300_000, 600_000, 8, 500_000, 300, &US_DOWN_RATES),
+                rx2: channel(923_300_000, 500_000, 300, &US_DOWN_RATES[..1]),
+            },
+            ProtoRegion::Au915 => FrequencyPlan {
+                uplink: [
+                    channels(915_200_000, 200_000, 64, 125_000, 300, &EU_RATES),
+                    channels(915_900_000, 1_600_000, 8, 500_000, 300, &[(8, 230)]),
+                ]
+                .concat(),
+                downlink: channels(923_300_000, 600_000, 8, 500_000, 300, &US_DOWN_RATES),
+                rx2: channel(923_300_000, 500_000, 300, &US_DOWN_RATES[..1]),
+            },
+            ProtoRegion::Cn470 => FrequencyPlan {
+                uplink: channels(470_300_000, 200_000, 96, 125_000, 191, &EU_RATES),
+                downlink: channels(500_300_000, 200_000, 48, 125_000, 191, &EU_RATES),
+                rx2: channel(505_300_000, 125_000, 191, &EU_RATES[..1]),
+            },
+            ProtoRegion::Eu868 => symmetric(
+                [
+                    channels(868_100_000, 200_000, 3, 125_000, 160, &EU_RATES),
+                    channels(867_100_000, 200_000, 5, 125_000, 160, &EU_RATES),
+                ]
+                .concat(),
+                channel(869_525_000, 125_000, 270, &EU_RATES[..1]),
+            ),
+            ProtoRegion::Eu433 => symmetric(
+                channels(433_175_000, 200_000, 3, 125_000, 121, &EU_RATES),
+                channel(434_665_000, 125_000, 121, &EU_RATES[..1]),
+            ),
+            ProtoRegion::Cn779 => symmetric(
+                channels(779_500_000, 200_000, 3, 125_000, 121, &EU_RATES),
+                channel(786_000_000, 125_000, 121, &EU_RATES[..1]),
+            ),
+            ProtoRegion::In865 => symmetric(
+                [865_062_500, 865_402_500, 865_985_000]
+                    .map(|frequency| channel(frequency, 125_000, 300, &EU_RATES))
+                    .to_vec(),
+                channel(866_550_000, 125_000, 300, &EU_RATES[2..3]),
+            ),
+            ProtoRegion::Kr920 => symmetric(
+                channels(
+                    922_100_000,
+                    200_000,
+                    3,
+                    125_000,
+                    140,
+                    &[(12, 65), (11, 65), (10, 65), (9, 151), (8, 250), (7, 250)],
+                ),
+                channel(921_900_000, 125_000, 140, &[(12, 65)]),
+            ),
+            ProtoRegion::As9231 => as923(0),
+            ProtoRegion::As9232 => as923(1_800_000),
+            ProtoRegion::As9233 => as923(6_600_000),
+            ProtoRegion::As9234 => as923(5_900_000),
+        }
+    }
+}
+
+/// The default channels of a region. See `Region::default_frequency_plan`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrequencyPlan {
+    /// Channels devices send uplinks on
+    pub uplink: Vec<RegionChannel>,
+    /// Channels downlinks are sent on in the first receive window, in the
+    /// region's uplink to downlink channel mapping order
+    pub downlink: Vec<RegionChannel>,
+    /// The channel and data rate of the second receive window
+    pub rx2: RegionChannel,
+}
+
+fn channel(frequency: u64, bandwidth: u64, max_eirp: u64, rates: &[(u8, u32)]) -> RegionChannel {
+    RegionChannel {
+        frequency,
+        bandwidth,
+        max_eirp,
+        data_rates: rates
+            .iter()
+            .map(|&(spreading_factor, max_packet_size)| DataRate {
+                spreading_factor,
+                max_packet_size,
+            })
+            .collect(),
+    }
+}
+
+/// count evenly spaced channels starting at first
+fn channels(
+    first: u64,
+    step: u64,
+    count: u64,
+    bandwidth: u64,
+    max_eirp: u64,
+    rates: &[(u8, u32)],
+) -> Vec<RegionChannel> {
+    (0..count)
+        .map(|n| channel(first + n * step, bandwidth, max_eirp, rates))
+        .collect()
+}
+
+/// The radio parameters for a region as handed out by validators
+#[derive(Debug, Clone)]
+pub struct RegionParams {
+    pub region: Region,
+    pub channels: Vec<RegionChannel>,
+}
+
+/// A single channel of a region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionChannel {
+    /// Center frequency in Hz
+    pub frequency: u64,
+    /// Bandwidth in Hz
+    pub bandwidth: u64,
+    /// Maximum EIRP in tenths of a dBm
+    pub max_eirp: u64,
+    pub data_rates: Vec<DataRate>,
+}
+
+/// A spreading factor usable on a channel, with the largest packet it can
+/// carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataRate {
+    pub spreading_factor: u8,
+    pub max_packet_size: u32,
+}
+
+impl RegionParams {
+    /// The center frequencies of all channels in Hz
+    pub fn frequencies(&self) -> Vec<u64> {
+        self.channels
+            .iter()
+            .map(|channel| channel.frequency)
+            .collect()
+    }
+
+    /// The highest maximum EIRP of any channel, in tenths of a dBm
+    pub fn max_eirp(&self) -> Option<u64> {
+        self.channels.iter().map(|channel| channel.max_eirp).max()
+    }
+
+    /// What changed from these params to other, channel by channel. A
+    /// change in the number of channels changes all channel fields.
+    pub fn diff(&self, other: &RegionParams) -> RegionParamsDiff {
+        fn changed<T: PartialEq>(
+            a: &RegionParams,
+            b: &RegionParams,
+            field: impl Fn(&RegionChannel) -> T,
+        ) -> bool {
+            !a.channels
+                .iter()
+                .map(&field)
+                .eq(b.channels.iter().map(&field))
+        }
+        RegionParamsDiff {
+            region: ProtoRegion::from(self.region) != ProtoRegion::from(other.region),
+            frequencies: changed(self, other, |channel| channel.frequency),
+            bandwidths: changed(self, other, |channel| channel.bandwidth),
+            max_eirp: changed(self, other, |channel| channel.max_eirp),
+            data_rates: changed(self, other, |channel| channel.data_rates.clone()),
+        }
+    }
+}
+
+/// Which parts of the region params changed between two updates. See
+/// `RegionParams::diff`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RegionParamsDiff {
+    pub region: bool,
+    pub frequencies: bool,
+    pub bandwidths: bool,
+    pub max_eirp: bool,
+    pub data_rates: bool,
+}
+
+impl RegionParamsDiff {
+    /// Whether nothing changed, so the radio doesn't need to be
+    /// reconfigured
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl TryFrom<GatewayRegionParamsStreamedRespV1> for RegionParams {
+    type Error = Error;
+    fn try_from(v: GatewayRegionParamsStreamedRespV1) -> Result<Self> {
+        let channels = v
+            .params
+            .map(|params| params.region_params)
+            .unwrap_or_default()
+            .into_iter()
+            .map(RegionChannel::try_from)
+            .collect::<Result<Vec<RegionChannel>>>()?;
+        Ok(Self {
+            region: Region::from_i32(v.region)?,
+            channels,
+        })
+    }
+}
+
+impl TryFrom<BlockchainRegionParamV1> for RegionChannel {
+    type Error = Error;
+    fn try_from(v: BlockchainRegionParamV1) -> Result<Self> {
+        let data_rates = v
+            .spreading
+            .map(|spreading| spreading.tagged_spreading)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tagged| {
+                Ok(DataRate {
+                    spreading_factor: spreading_factor(tagged.region_spreading)?,
+                    max_packet_size: tagged.max_packet_size,
+                })
+            })
+            .collect::<Result<Vec<DataRate>>>()?;
+        Ok(Self {
+            frequency: v.channel_frequency,
+            bandwidth: v.bandwidth,
+            max_eirp: v.max_eirp,
+            data_rates,
+        })
+    }
+}
+
+fn spreading_factor(v: i32) -> Result<u8> {
+    match RegionSpreading::from_i32(v) {
+        Some(RegionSpreading::Sf7) => Ok(7),
+        Some(RegionSpreading::Sf8) => Ok(8),
+        Some(RegionSpreading::Sf9) => Ok(9),
+        Some(RegionSpreading::Sf10) => Ok(10),
+        Some(RegionSpreading::Sf11) => Ok(11),
+        Some(RegionSpreading::Sf12) => Ok(12),
+        _ => Err(Error::custom(format!("unsupported spreading {v}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_params() {
+        use helium_proto::{
+            BlockchainRegionParamsV1, BlockchainRegionSpreadingV1, TaggedSpreading,
+        };
+        let spreading = BlockchainRegionSpreadingV1 {
+            tagged_spreading: [
+                (RegionSpreading::Sf10, 24),
+                (RegionSpreading::Sf9, 53),
+                (RegionSpreading::Sf8, 125),
+                (RegionSpreading::Sf7, 242),
+            ]
+            .map(|(sf, max_packet_size)| TaggedSpreading {
+                region_spreading: sf.into(),
+                max_packet_size,
+            })
+            .to_vec(),
+        };
+        let region_params = [903_900_000, 904_100_000]
+            .map(|channel_frequency| BlockchainRegionParamV1 {
+                channel_frequency,
+                bandwidth: 125_000,
+                max_eirp: 360,
+                spreading: Some(spreading.clone()),
+            })
+            .to_vec();
+        let params = RegionParams::try_from(GatewayRegionParamsStreamedRespV1 {
+            region: ProtoRegion::Us915.into(),
+            params: Some(BlockchainRegionParamsV1 { region_params }),
+            ..Default::default()
+        })
+        .expect("region params");
+        assert_eq!("US915", params.region.to_string());
+        assert_eq!(vec![903_900_000, 904_100_000], params.frequencies());
+        assert_eq!(Some(360), params.max_eirp());
+        assert_eq!(10, params.channels[0].data_rates[0].spreading_factor);
+        assert_eq!(242, params.channels[1].data_rates[3].max_packet_size);
+    }
+
+    #[test]
+    fn region_params_diff() {
+        let channel = |frequency| RegionChannel {
+            frequency,
+            bandwidth: 125_000,
+            max_eirp: 360,
+            data_rates: vec![DataRate {
+                spreading_factor: 10,
+                max_packet_size: 24,
+            }],
+        };
+        let params = |channels| RegionParams {
+            region: Region(ProtoRegion::Us915),
+            channels,
+        };
+        let current = params(vec![channel(903_900_000), channel(904_100_000)]);
+        assert!(current.diff(&current.clone()).is_empty());
+
+        let mut update = current.clone();
+        update.channels[1].max_eirp = 300;
+        let diff = current.diff(&update);
+        assert!(diff.max_eirp);
+        assert!(!diff.frequencies && !diff.bandwidths && !diff.data_rates && !diff.region);
+
+        update.channels[0].data_rates[0].max_packet_size = 53;
+        assert!(current.diff(&update).data_rates);
+
+        let diff = current.diff(&params(vec![channel(903_900_000)]));
+        assert!(diff.frequencies && diff.bandwidths && diff.max_eirp && diff.data_rates);
+    }
+
+    #[test]
+    fn unknown_region() {
+        assert!(matches!(
+            Region::from_i32(9999),
+            Err(Error::Decode(DecodeError::UnknownRegion(9999)))
+        ));
+        assert!(matches!(
+            RegionParams::try_from(GatewayRegionParamsStreamedRespV1 {
+                region: 9999,
+                ..Default::default()
+            }),
+            Err(Error::Decode(DecodeError::UnknownRegion(9999)))
+        ));
+    }
+
+    #[test]
+    fn default_frequency_plan() {
+        let plan = Region(ProtoRegion::Us915).default_frequency_plan();
+        assert_eq!(72, plan.uplink.len());
+        assert_eq!(8, plan.downlink.len());
+        assert_eq!(902_300_000, plan.uplink[0].frequency);
+        assert_eq!(914_900_000, plan.uplink[63].frequency);
+        assert_eq!(500_000, plan.uplink[64].bandwidth);
+        assert_eq!(927_500_000, plan.downlink[7].frequency);
+        assert_eq!(12, plan.rx2.data_rates[0].spreading_factor);
+
+        let plan = Region(ProtoRegion::Eu868).default_frequency_plan();
+        assert_eq!(8, plan.uplink.len());
+        assert_eq!(plan.uplink, plan.downlink);
+        assert_eq!(868_100_000, plan.uplink[0].frequency);
+        assert!(plan
+            .uplink
+            .iter()
+            .all(|channel| channel.bandwidth == 125_000 && channel.data_rates.len() == 6));
+        assert_eq!(869_525_000, plan.rx2.frequency);
+
+        let plan = Region(ProtoRegion::As9232).default_frequency_plan();
+        assert_eq!(
+            vec![921_400_000, 921_600_000],
+            plan.uplink
+                .iter()
+                .map(|channel| channel.frequency)
+                .collect::<Vec<_>>()
+        );
     }
 }
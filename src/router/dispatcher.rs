@@ -155,8 +155,44 @@ impl Dispatcher {
             GATEWAY_BACKOFF_MIN_WAIT,
             GATEWAY_BACKOFF_MAX_WAIT,
         );
+        if self.gateways.is_empty() {
+            return Err(Error::custom("empty uri list"));
+        }
         loop {
-            let gateway = GatewayService::random_new(&self.gateways)?;
+            // Probing the gateways can take a while, so keep handling
+            // messages that don't require a gateway in the meantime
+            let gateway = {
+                let random_gateway = GatewayService::random_new(&self.gateways);
+                tokio::pin!(random_gateway);
+                let mut messages_closed = false;
+                loop {
+                    tokio::select! {
+                        _ = shutdown.clone() => {
+                            info!(logger, "shutting down");
+                            return Ok(())
+                        },
+                        gateway = &mut random_gateway => break gateway,
+                        message = self.messages.recv(), if !messages_closed => match message {
+                            Some(message) => self.handle_message(message, None, &logger).await,
+                            None => {
+                                warn!(logger, "ignoring closed messages channel");
+                                messages_closed = true;
+                            }
+                        }
+                    }
+                }
+            };
+            let gateway = match gateway {
+                Ok(gateway) => gateway,
+                Err(err) => {
+                    warn!(logger, "no gateway available: {err:?}");
+                    self.prepare_gateway_change(&gateway_backoff, shutdown.clone(), &logger).await;
+                    if shutdown.is_triggered() {
+                        return Ok(())
+                    }
+                    continue;
+                }
+            };
             info!(logger, "using gateway";
                 "pubkey" => gateway.uri.pubkey.to_string(),
                 "uri" => gateway.uri.uri.to_string());
@@ -251,16 +287,12 @@ impl Dispatcher {
     }
 
     async fn check_gateway(&mut self, gateway: &mut GatewayService, logger: &Logger) -> Result {
-        let (_, block_age) = gateway.height().await?;
+        let (_, block_age) = gateway
+            .height_checked(GATEWAY_MAX_BLOCK_AGE.as_secs())
+            .await?;
         info!(logger, "checking gateway"; 
             "pubkey" => gateway.uri.pubkey.to_string(),
             "block_age" => block_age);
-        if block_age > GATEWAY_MAX_BLOCK_AGE.as_secs() {
-            return Err(Error::gateway_service_check(
-                block_age,
-                GATEWAY_MAX_BLOCK_AGE.as_secs(),
-            ));
-        }
         Ok(())
     }
 
@@ -1,5 +1,6 @@
 use bytes::{Buf, BufMut};
 use helium_proto::Eui;
+use lorawan::subnet::{DevAddr, DevAddrRange, NetId};
 use std::{fmt, hash::Hasher, sync::Arc};
 use xorf::{Filter as XorFilter, Xor16};
 use xxhash_c::XXH64;
@@ -69,6 +70,40 @@ impl DevAddrFilter {
         let addr_base = (BITS_23 as u32) & devaddr;
         addr_base >= self.base && addr_base < (self.base + self.size)
     }
+
+    /// Whether any of the subnet addresses in `[lower, upper)` pass this
+    /// filter
+    pub fn intersects(&self, lower: u32, upper: u32) -> bool {
+        self.base < upper && lower < (self.base + self.size)
+    }
+
+    /// The devaddrs whose subnet addresses in netid_list pass this filter, as
+    /// one range for each NetID the filter overlaps
+    pub fn devaddr_ranges(&self, netid_list: &[NetId]) -> Vec<DevAddrRange> {
+        netid_list
+            .iter()
+            .filter_map(|netid| {
+                let (netid_lower, netid_upper) = netid.addr_range(netid_list)?;
+                let netid_lower = u32::from(netid_lower);
+                let lower = netid_lower.max(self.base);
+                let upper = u32::from(netid_upper).min(self.base + self.size);
+                if lower >= upper {
+                    return None;
+                }
+                let start = netid.to_devaddr(lower - netid_lower);
+                let end = u32::from(start) + (upper - lower);
+                Some(DevAddrRange {
+                    start,
+                    end: DevAddr::from(end),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether every subnet address passing other also passes this filter
+    pub fn covers(&self, other: &DevAddrFilter) -> bool {
+        other.base >= self.base && other.base + other.size <= self.base + self.size
+    }
 }
 
 #[cfg(test)]
@@ -94,6 +129,42 @@ mod tests {
             assert_eq!(8, filter.size);
             assert!(filter.contains(&2063));
         }
+
+        #[test]
+        fn intersects() {
+            static MASK: [u8; 6] = [0, 4, 4, 127, 255, 254];
+            let filter = DevAddrFilter::from_bin(&MASK);
+            assert!(filter.intersects(0, 2057));
+            assert!(filter.intersects(2063, 4096));
+            assert!(!filter.intersects(0, 2056));
+            assert!(!filter.intersects(2064, 4096));
+        }
+
+        #[test]
+        fn devaddr_ranges() {
+            // Subnet addresses 992..1056, across the end of the first NetID
+            let filter = DevAddrFilter::from_bin([0, 1, 240, 127, 255, 240]);
+            let netids = [NetId::from(0xC00035), NetId::from(0xE00001)];
+            assert_eq!(1024, netids[0].size());
+            let ranges = filter.devaddr_ranges(&netids);
+            assert_eq!(2, ranges.len());
+            assert_eq!(netids[0].to_devaddr(992), ranges[0].start);
+            assert_eq!(32, ranges[0].len());
+            assert_eq!(netids[1].to_devaddr(0), ranges[1].start);
+            assert_eq!(32, ranges[1].len());
+
+            let filter = DevAddrFilter::from_bin([0, 4, 0, 127, 255, 240]);
+            assert!(filter.devaddr_ranges(&netids).is_empty());
+        }
+
+        #[test]
+        fn covers() {
+            let filter = DevAddrFilter::from_bin([0, 4, 0, 127, 255, 240]);
+            let nested = DevAddrFilter::from_bin([0, 4, 4, 127, 255, 254]);
+            assert!(filter.covers(&nested));
+            assert!(filter.covers(&filter));
+            assert!(!nested.covers(&filter));
+        }
     }
 
     mod eui {
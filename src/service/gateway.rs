@@ -1,39 +1,400 @@
-use crate::{service::CONNECT_TIMEOUT, Error, KeyedUri, MsgSign, MsgVerify, Region, Result};
+use crate::{
+    error::{GatewayError, ServiceError},
+    router::DevAddrFilter,
+    service::{CONNECT_TIMEOUT, RPC_TIMEOUT},
+    traits::sign_encoded,
+    Error, KeyedUri, MsgSign, MsgVerify, Region, RegionParams, Result,
+};
+use exponential_backoff::Backoff;
+use futures::{Future, Stream, StreamExt};
 use helium_crypto::{Keypair, PublicKey};
 use helium_proto::{
     gateway_resp_v1,
     services::{self, Channel, Endpoint},
-    BlockchainTxnStateChannelCloseV1, BlockchainVarV1, GatewayConfigReqV1, GatewayConfigRespV1,
-    GatewayRegionParamsUpdateReqV1, GatewayRespV1, GatewayRoutingReqV1, GatewayScCloseReqV1,
-    GatewayScFollowReqV1, GatewayScFollowStreamedRespV1, GatewayScIsActiveReqV1,
-    GatewayScIsActiveRespV1, Routing,
+    BlockchainTxnStateChannelCloseV1, BlockchainVarV1, CloseState, GatewayConfigReqV1,
+    GatewayConfigRespV1, GatewayRegionParamsUpdateReqV1, GatewayRespV1, GatewayRoutingReqV1,
+    GatewayScCloseReqV1, GatewayScCloseRespV1, GatewayScFollowReqV1, GatewayScFollowStreamedRespV1,
+    GatewayScIsActiveReqV1, GatewayScIsActiveRespV1, Message, Routing,
+};
+use http::Uri;
+use lorawan::subnet::{DevAddrRange, NetId};
+use rand::{rngs::OsRng, seq::SliceRandom, Rng};
+use slog::{debug, o, warn, Logger};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
-use rand::{rngs::OsRng, seq::SliceRandom};
-use std::{sync::Arc, time::Duration};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
 use tokio_stream::wrappers::ReceiverStream;
+use tonic::metadata::{Ascii, MetadataKey, MetadataValue};
+#[cfg(feature = "tls")]
+use tonic::transport::ClientTlsConfig;
 
 type GatewayClient = services::gateway::Client<Channel>;
 
+/// A gateway client on the given channel. Every client is built here so
+/// that client wide settings apply to unary and streaming calls alike.
+fn new_client(channel: Channel) -> GatewayClient {
+    GatewayClient::new(channel)
+}
+
+/// Uris with this scheme name a unix socket by their path
+const UNIX_SCHEME: &str = "unix";
+
+fn is_unix(uri: &Uri) -> bool {
+    uri.scheme_str() == Some(UNIX_SCHEME)
+}
+
+/// A channel for the endpoint that connects on first use, to the unix socket
+/// at the path of unix uris.
+fn lazy_channel(uri: &Uri, endpoint: &Endpoint) -> Channel {
+    #[cfg(unix)]
+    if is_unix(uri) {
+        let path = PathBuf::from(uri.path());
+        return endpoint.connect_with_connector_lazy(tower::service_fn(move |_: Uri| {
+            tokio::net::UnixStream::connect(path.clone())
+        }));
+    }
+    endpoint.connect_lazy()
+}
+
+/// Like `lazy_channel` but connects right away
+async fn eager_channel(uri: &Uri, endpoint: &Endpoint) -> Result<Channel> {
+    #[cfg(unix)]
+    if is_unix(uri) {
+        let path = PathBuf::from(uri.path());
+        let channel = endpoint
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                tokio::net::UnixStream::connect(path.clone())
+            }))
+            .await?;
+        return Ok(channel);
+    }
+    Ok(endpoint.connect().await?)
+}
+
+/// The uris in a random order where each next uri is picked with a
+/// probability proportional to its weight among the remaining ones. Once only
+/// zero weights remain, the rest are shuffled uniformly.
+fn weighted_order<F, R>(uris: &[KeyedUri], weight_fn: F, rng: &mut R) -> Vec<KeyedUri>
+where
+    F: Fn(&KeyedUri) -> f64,
+    R: Rng,
+{
+    let mut remaining: Vec<(KeyedUri, f64)> = uris
+        .iter()
+        .map(|uri| (uri.clone(), weight_fn(uri).max(0.0)))
+        .collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let total: f64 = remaining.iter().map(|(_, weight)| weight).sum();
+        if !(total > 0.0 && total.is_finite()) {
+            remaining.shuffle(rng);
+            ordered.extend(remaining.drain(..).map(|(uri, _)| uri));
+            break;
+        }
+        let mut pick = rng.gen_range(0.0..total);
+        let index = remaining
+            .iter()
+            .position(|(_, weight)| {
+                pick -= weight;
+                pick < 0.0
+            })
+            .unwrap_or(remaining.len() - 1);
+        ordered.push(remaining.remove(index).0);
+    }
+    ordered
+}
+
+/// Await a request, failing with a `DeadlineExceeded` status if it doesn't
+/// complete within timeout
+async fn deadline<T, F>(logger: &Logger, timeout: Duration, request: F) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    match tokio::time::timeout(timeout, request).await {
+        Ok(result) => result,
+        Err(_) => logged(
+            logger,
+            Err(tonic::Status::deadline_exceeded(format!("no response within {timeout:?}")).into()),
+        ),
+    }
+}
+
+/// Log the outcome of a request at debug level
+fn logged<T>(logger: &Logger, result: Result<T>) -> Result<T> {
+    match &result {
+        Ok(_) => debug!(logger, "ok"),
+        Err(err) => debug!(logger, "failed: {err}"),
+    }
+    result
+}
+
+/// The routings of each response in a routing stream, one at a time and in
+/// order. A response that isn't a routing response, or fails, is passed on
+/// as an error.
+fn flat_routings<S>(stream: S) -> impl Stream<Item = Result<Routing>>
+where
+    S: Stream<Item = Result<Response>>,
+{
+    stream.flat_map(|response| {
+        let routings = match response.and_then(|response| Ok(response.routings()?.to_vec())) {
+            Ok(routings) => routings.into_iter().map(Ok).collect(),
+            Err(err) => vec![Err(err)],
+        };
+        futures::stream::iter(routings)
+    })
+}
+
+fn verify_response(logger: &Logger, response: &GatewayRespV1, verifier: &PublicKey) -> Result {
+    response.verify(verifier).map_err(|err| {
+        debug!(logger, "signature verification failed");
+        err
+    })
+}
+
 #[derive(Debug)]
 pub struct Streaming {
     streaming: tonic::Streaming<GatewayRespV1>,
     verifier: Arc<PublicKey>,
+    resume: Option<ResumePosition>,
+    logger: Logger,
+    messages: u64,
+    height_check: Option<HeightCheck>,
+    shutdown: Option<triggered::Listener>,
+}
+
+/// Tracks the highest height seen on a stream, rejecting heights that fall
+/// more than `tolerance` blocks below it.
+#[derive(Debug, Clone, Copy)]
+struct HeightCheck {
+    tolerance: u64,
+    last: Option<u64>,
+}
+
+impl HeightCheck {
+    fn new(tolerance: u64) -> Self {
+        Self {
+            tolerance,
+            last: None,
+        }
+    }
+
+    fn check(&mut self, height: u64) -> Result {
+        match self.last {
+            Some(last) if height.saturating_add(self.tolerance) < last => {
+                Err(GatewayError::height_regression(last, height))
+            }
+            Some(last) if height <= last => Ok(()),
+            _ => {
+                self.last = Some(height);
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Response(GatewayRespV1);
 
+/// The position a stream can be resumed from after a reconnect.
+///
+/// The gateway protocol only supports resuming from a block height today.
+/// Opaque server issued tokens are modeled so that reconnect logic can carry
+/// them once validators hand them out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumePosition {
+    Height(u64),
+    Token(Vec<u8>),
+}
+
+impl ResumePosition {
+    pub fn height(&self) -> Result<u64> {
+        match self {
+            Self::Height(height) => Ok(*height),
+            Self::Token(_) => Err(Error::custom("resume tokens not supported")),
+        }
+    }
+}
+
 impl Streaming {
+    fn new(
+        streaming: tonic::Streaming<GatewayRespV1>,
+        verifier: Arc<PublicKey>,
+        logger: Logger,
+    ) -> Self {
+        Self {
+            streaming,
+            verifier,
+            resume: None,
+            logger,
+            messages: 0,
+            height_check: None,
+            shutdown: None,
+        }
+    }
+
+    /// End this stream once the given shutdown listener is triggered, even
+    /// while waiting for the next message. Dropping the ended stream closes
+    /// the underlying request.
+    pub fn with_shutdown(mut self, shutdown: triggered::Listener) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Reject responses whose height is more than tolerance blocks below the
+    /// highest height seen on this stream, as a validator replaying old
+    /// blocks would send. A rejected response is yielded as a
+    /// `GatewayError::HeightRegression` error and does not move the resume
+    /// position.
+    pub fn with_monotonic_height(mut self, tolerance: u64) -> Self {
+        self.height_check = Some(HeightCheck::new(tolerance));
+        self
+    }
+
     pub async fn message(&mut self) -> Result<Option<Response>> {
-        match self.streaming.message().await {
-            Ok(Some(response)) => {
-                response.verify(&self.verifier)?;
-                Ok(Some(Response(response)))
+        self.next().await.transpose()
+    }
+
+    fn verified(&mut self, response: GatewayRespV1) -> Result<Response> {
+        verify_response(&self.logger, &response, &self.verifier)?;
+        if let Some(height_check) = self.height_check.as_mut() {
+            height_check.check(response.height)?;
+        }
+        self.resume = Some(ResumePosition::Height(response.height));
+        self.messages += 1;
+        Ok(Response(response))
+    }
+
+    /// The number of verified messages received on this stream so far
+    pub fn message_count(&self) -> u64 {
+        self.messages
+    }
+
+    /// Returns the position of the last verified message received on this
+    /// stream, if any.
+    pub fn resume_position(&self) -> Option<&ResumePosition> {
+        self.resume.as_ref()
+    }
+
+    /// Read this stream ahead of its consumer in a spawned task, holding up
+    /// to capacity received responses until they are consumed. A capacity of
+    /// 0 is taken as 1.
+    ///
+    /// Without a buffer a consumer that falls behind stops reading the
+    /// stream, which lets h2 flow control stall the whole connection. The
+    /// reader keeps receiving and verifying responses until the buffer is
+    /// full. Every buffered response is held in memory, so the buffer costs
+    /// up to capacity times the size of the largest response, which for
+    /// routing responses during a large resync can be many routings each.
+    pub fn buffered(self, capacity: usize) -> BufferedStreaming {
+        read_ahead(self, capacity)
+    }
+}
+
+/// Yields verified responses. A response that fails signature verification
+/// is yielded as an error; the end of the underlying stream, or a triggered
+/// shutdown listener, ends this one.
+impl Stream for Streaming {
+    type Item = Result<Response>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cxt: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(shutdown) = self.shutdown.as_mut() {
+            if Pin::new(shutdown).poll(cxt).is_ready() {
+                debug!(self.logger, "stream shut down"; "messages" => self.messages);
+                return Poll::Ready(None);
+            }
+        }
+        match Pin::new(&mut self.streaming).poll_next(cxt) {
+            Poll::Ready(Some(Ok(response))) => Poll::Ready(Some(self.verified(response))),
+            Poll::Ready(Some(Err(err))) => {
+                debug!(self.logger, "stream failed: {err}"; "messages" => self.messages);
+                Poll::Ready(Some(Err(err.into())))
+            }
+            Poll::Ready(None) => {
+                debug!(self.logger, "stream ended"; "messages" => self.messages);
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A stream read ahead of its consumer. See `Streaming::buffered`.
+///
+/// Dropping it stops the reader and closes the underlying request.
+#[derive(Debug)]
+pub struct BufferedStreaming {
+    rx: ReceiverStream<Result<Response>>,
+    reader: tokio::task::JoinHandle<()>,
+    resume: Option<ResumePosition>,
+    messages: u64,
+}
+
+fn read_ahead<S>(stream: S, capacity: usize) -> BufferedStreaming
+where
+    S: Stream<Item = Result<Response>> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    let reader = tokio::spawn(async move {
+        let mut stream = Box::pin(stream);
+        while let Some(item) = stream.next().await {
+            if tx.send(item).await.is_err() {
+                break;
             }
-            Ok(None) => Ok(None),
-            Err(err) => Err(err.into()),
         }
+    });
+    BufferedStreaming {
+        rx: ReceiverStream::new(rx),
+        reader,
+        resume: None,
+        messages: 0,
+    }
+}
+
+impl BufferedStreaming {
+    pub async fn message(&mut self) -> Result<Option<Response>> {
+        self.next().await.transpose()
+    }
+
+    /// The number of verified messages consumed from this stream so far
+    pub fn message_count(&self) -> u64 {
+        self.messages
+    }
+
+    /// Returns the position of the last verified message consumed from this
+    /// stream, if any. Responses still in the buffer are not included, so
+    /// resuming from here receives them again.
+    pub fn resume_position(&self) -> Option<&ResumePosition> {
+        self.resume.as_ref()
+    }
+}
+
+impl Stream for BufferedStreaming {
+    type Item = Result<Response>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cxt: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = futures::ready!(Pin::new(&mut self.rx).poll_next(cxt));
+        if let Some(Ok(response)) = &item {
+            self.resume = Some(ResumePosition::Height(response.height()));
+            self.messages += 1;
+        }
+        Poll::Ready(item)
+    }
+}
+
+impl Drop for BufferedStreaming {
+    fn drop(&mut self) {
+        self.reader.abort();
     }
 }
 
@@ -42,45 +403,493 @@ impl Response {
         self.0.height
     }
 
+    /// The age in seconds of the validator's last block when it sent this
+    /// response
+    pub fn block_age(&self) -> u64 {
+        self.0.block_age
+    }
+
     pub fn routings(&self) -> Result<&[Routing]> {
         match &self.0.msg {
             Some(gateway_resp_v1::Msg::RoutingStreamedResp(routings)) => Ok(&routings.routings),
-            msg => Err(Error::custom(
-                format!("Unexpected gateway message {msg:?}",),
-            )),
+            msg => Err(GatewayError::unexpected_message("routing", msg)),
+        }
+    }
+
+    /// The routings in a routing response that can route devaddrs of any of
+    /// the NetIDs in netid_list.
+    ///
+    /// The subnets of a routing are devaddr filters over Helium subnet
+    /// addresses, each passing a range `[base, base + size)`. A NetID's
+    /// devaddrs occupy the subnet addresses `NetId::addr_range` returns for
+    /// netid_list, which are those `SubnetAddr::within_range` accepts for that
+    /// NetID. A routing is kept if one of its subnets overlaps one of those
+    /// ranges.
+    pub fn local_routings(&self, netid_list: &[NetId]) -> Result<Vec<Routing>> {
+        let ranges: Vec<(u32, u32)> = netid_list
+            .iter()
+            .filter_map(|netid| netid.addr_range(netid_list))
+            .map(|(lower, upper)| (lower.into(), upper.into()))
+            .collect();
+        let local = self
+            .routings()?
+            .iter()
+            .filter(|routing| {
+                routing
+                    .subnets
+                    .iter()
+                    .map(DevAddrFilter::from_bin)
+                    .any(|filter| {
+                        ranges
+                            .iter()
+                            .any(|(lower, upper)| filter.intersects(*lower, *upper))
+                    })
+            })
+            .cloned()
+            .collect();
+        Ok(local)
+    }
+
+    /// The routings in a routing response with those for the same OUI merged
+    /// into one, in order of first appearance.
+    ///
+    /// Merged routings keep each distinct address and filter once, and drop
+    /// subnets that another subnet of the OUI already covers, keeping the
+    /// first of identical subnets.
+    pub fn deduped_routings(&self) -> Result<Vec<Routing>> {
+        let mut merged: Vec<Routing> = Vec::new();
+        for routing in self.routings()? {
+            match merged.iter_mut().find(|entry| entry.oui == routing.oui) {
+                Some(entry) => {
+                    extend_distinct(&mut entry.addresses, &routing.addresses);
+                    extend_distinct(&mut entry.filters, &routing.filters);
+                    entry.subnets.extend_from_slice(&routing.subnets);
+                }
+                None => merged.push(routing.clone()),
+            }
+        }
+        for routing in merged.iter_mut() {
+            routing.subnets = collapse_subnets(&routing.subnets);
         }
+        Ok(merged)
     }
 
+    /// The region in a region params response. Fails with
+    /// `DecodeError::UnknownRegion` for regions this gateway doesn't know.
     pub fn region(&self) -> Result<Region> {
+        Region::from_i32(self.region_code()?)
+    }
+
+    /// The raw region code in a region params response, whether or not it
+    /// maps to a known `Region`
+    pub fn region_code(&self) -> Result<i32> {
+        match &self.0.msg {
+            Some(gateway_resp_v1::Msg::RegionParamsStreamedResp(params)) => Ok(params.region),
+            msg => Err(GatewayError::unexpected_message("region_params", msg)),
+        }
+    }
+
+    /// The full set of radio parameters in a region params response
+    pub fn region_params(&self) -> Result<RegionParams> {
         match &self.0.msg {
             Some(gateway_resp_v1::Msg::RegionParamsStreamedResp(params)) => {
-                Region::from_i32(params.region)
+                RegionParams::try_from(params.clone())
             }
-            msg => Err(Error::custom(
-                format!("Unexpected gateway message {msg:?}",),
-            )),
+            msg => Err(GatewayError::unexpected_message("region_params", msg)),
+        }
+    }
+}
+
+/// A decoded chain variable value. See `GatewayService::config_typed`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigValue {
+    Int(i64),
+    Float(f64),
+    Atom(String),
+    Binary(Vec<u8>),
+}
+
+impl TryFrom<&BlockchainVarV1> for ConfigValue {
+    type Error = Error;
+
+    fn try_from(v: &BlockchainVarV1) -> Result<Self> {
+        let name = &v.name;
+        let str_value = || {
+            std::str::from_utf8(&v.value)
+                .map_err(|_| Error::custom(format!("not a valid value: {name}")))
+        };
+        match v.r#type.as_str() {
+            "int" => str_value()?
+                .parse()
+                .map(Self::Int)
+                .map_err(|_| Error::custom(format!("not a valid int value: {name}"))),
+            "float" => str_value()?
+                .parse()
+                .map(Self::Float)
+                .map_err(|_| Error::custom(format!("not a valid float value: {name}"))),
+            "atom" => Ok(Self::Atom(str_value()?.to_string())),
+            "binary" => Ok(Self::Binary(v.value.clone())),
+            other => Err(Error::custom(format!(
+                "unsupported variable type {other}: {name}"
+            ))),
+        }
+    }
+}
+
+/// The well-known state channel chain variables. Variables the validator
+/// didn't return are left at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChainVars {
+    pub sc_version: u64,
+    pub sc_grace_blocks: u64,
+    pub sc_max_actors: u64,
+    pub sc_dispute_strategy_version: u64,
+    pub max_open_sc: u64,
+    pub min_expire_within: u64,
+    pub dc_payload_size: u64,
+}
+
+impl ChainVars {
+    /// The keys to request from a validator to populate all fields
+    pub const KEYS: &'static [&'static str] = &[
+        "sc_version",
+        "sc_grace_blocks",
+        "sc_max_actors",
+        "sc_dispute_strategy_version",
+        "max_open_sc",
+        "min_expire_within",
+        "dc_payload_size",
+    ];
+
+    pub fn keys() -> Vec<String> {
+        Self::KEYS.iter().map(|key| key.to_string()).collect()
+    }
+}
+
+impl TryFrom<Vec<BlockchainVarV1>> for ChainVars {
+    type Error = Error;
+
+    fn try_from(vars: Vec<BlockchainVarV1>) -> Result<Self> {
+        let mut result = Self::default();
+        for var in vars.iter() {
+            let field = match var.name.as_str() {
+                "sc_version" => &mut result.sc_version,
+                "sc_grace_blocks" => &mut result.sc_grace_blocks,
+                "sc_max_actors" => &mut result.sc_max_actors,
+                "sc_dispute_strategy_version" => &mut result.sc_dispute_strategy_version,
+                "max_open_sc" => &mut result.max_open_sc,
+                "min_expire_within" => &mut result.min_expire_within,
+                "dc_payload_size" => &mut result.dc_payload_size,
+                _ => continue,
+            };
+            *field = match ConfigValue::try_from(var)? {
+                ConfigValue::Int(value) if value >= 0 => value as u64,
+                _ => {
+                    return Err(Error::custom(format!(
+                        "not a valid unsigned int value: {}",
+                        var.name
+                    )))
+                }
+            };
+        }
+        Ok(result)
+    }
+}
+
+/// Estimated offset of the local clock relative to a validator's clock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSkew {
+    /// Local time is ahead of the validator by the given duration
+    Ahead(Duration),
+    /// Local time is behind the validator by the given duration
+    Behind(Duration),
+}
+
+/// HTTP/2 keep-alive settings for the connection to a validator.
+///
+/// Streaming requests like `routing` and `follow_sc` can go quiet for long
+/// stretches, during which NAT gateways may silently drop the connection.
+/// Keep-alive pings are sent every `interval` while a request or stream is
+/// open, and a connection that doesn't acknowledge one within `timeout` is
+/// closed, ending its streams with an error instead of leaving them hanging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepAliveConfig {
+    /// How often to ping the validator
+    pub interval: Duration,
+    /// How long to wait for a ping to be acknowledged
+    pub timeout: Duration,
+    /// Whether to keep pinging while no requests or streams are open
+    pub while_idle: bool,
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(60),
+            timeout: Duration::from_secs(20),
+            while_idle: false,
+        }
+    }
+}
+
+impl KeepAliveConfig {
+    fn apply(&self, endpoint: Endpoint) -> Endpoint {
+        endpoint
+            .http2_keep_alive_interval(self.interval)
+            .keep_alive_timeout(self.timeout)
+            .keep_alive_while_idle(self.while_idle)
+    }
+}
+
+/// How to retry unary gateway requests that fail with a retryable error.
+///
+/// Only `tonic::Status` errors with code `Unavailable` or `DeadlineExceeded`
+/// are retried. Every other error, including unexpected or empty responses,
+/// is returned right away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Delay before the first retry, doubling with every further retry
+    pub base_delay: Duration,
+    /// Upper bound on the delay between retries
+    pub max_delay: Duration,
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Random jitter applied to each delay, as a fraction between 0 and 1
+    pub jitter: f32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_attempts: 5,
+            jitter: 0.3,
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn is_retryable(err: &Error) -> bool {
+        match err {
+            Error::Service(ServiceError::Rpc(status)) => matches!(
+                status.code(),
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded
+            ),
+            _ => false,
+        }
+    }
+
+    async fn retry<T, F, Fut>(
+        &self,
+        logger: &Logger,
+        budget: Option<&RetryBudget>,
+        mut f: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = Backoff::new(self.max_attempts, self.base_delay, self.max_delay);
+        backoff.set_jitter(self.jitter);
+        let mut attempt = 1;
+        loop {
+            match f().await {
+                Err(err) if Self::is_retryable(&err) && attempt < self.max_attempts => {
+                    if !budget.map_or(true, RetryBudget::try_spend) {
+                        debug!(logger, "retry budget exhausted, failing after {err}";
+                            "attempt" => attempt);
+                        return Err(err);
+                    }
+                    let delay = backoff.next(attempt).unwrap_or(self.max_delay);
+                    debug!(logger, "retrying after {err}";
+                        "attempt" => attempt,
+                        "delay_ms" => delay.as_millis() as u64);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Caps the retries of a service and its clones across all requests, so
+/// that retries can't pile onto a struggling validator. The budget holds up
+/// to max retries and refills continuously at max retries per period. Once
+/// it is spent, retryable failures are returned right away until it refills.
+#[derive(Debug)]
+pub struct RetryBudget {
+    max: f64,
+    period: Duration,
+    bucket: Mutex<RetryBucket>,
+}
+
+#[derive(Debug)]
+struct RetryBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RetryBudget {
+    pub fn new(max: u32, period: Duration) -> Self {
+        Self {
+            max: max as f64,
+            period,
+            bucket: Mutex::new(RetryBucket {
+                tokens: max as f64,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a retry from the budget, if there's one left
+    fn try_spend(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|err| err.into_inner());
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        let refill = elapsed * self.max / self.period.as_secs_f64().max(f64::EPSILON);
+        bucket.tokens = (bucket.tokens + refill).min(self.max);
+        bucket.refilled = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// A state channel follow update, by the close state it reports
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScFollowEvent {
+    /// The state channel has expired and may be closed
+    Closable(GatewayScFollowStreamedRespV1),
+    /// The state channel is in its grace period, closes filed now are
+    /// still considered
+    Closing(GatewayScFollowStreamedRespV1),
+    /// The state channel is closed
+    Closed(GatewayScFollowStreamedRespV1),
+    /// The state channel close is being disputed
+    Dispute(GatewayScFollowStreamedRespV1),
+    /// A close state this build doesn't know, as a newer validator may
+    /// report. Nothing should be assumed about the state channel.
+    Unknown(i32, GatewayScFollowStreamedRespV1),
+}
+
+impl From<GatewayScFollowStreamedRespV1> for ScFollowEvent {
+    fn from(resp: GatewayScFollowStreamedRespV1) -> Self {
+        // Not the close_state() getter, which takes unknown states for the
+        // default Closable
+        match CloseState::from_i32(resp.close_state) {
+            Some(CloseState::Closable) => Self::Closable(resp),
+            Some(CloseState::Closing) => Self::Closing(resp),
+            Some(CloseState::Closed) => Self::Closed(resp),
+            Some(CloseState::Dispute) => Self::Dispute(resp),
+            None => Self::Unknown(resp.close_state, resp),
+        }
+    }
+}
+
+impl ScFollowEvent {
+    pub fn sc_id(&self) -> &[u8] {
+        &self.as_inner().sc_id
+    }
+
+    pub fn as_inner(&self) -> &GatewayScFollowStreamedRespV1 {
+        match self {
+            Self::Closable(resp)
+            | Self::Closing(resp)
+            | Self::Closed(resp)
+            | Self::Dispute(resp)
+            | Self::Unknown(_, resp) => resp,
+        }
+    }
+
+    pub fn into_inner(self) -> GatewayScFollowStreamedRespV1 {
+        match self {
+            Self::Closable(resp)
+            | Self::Closing(resp)
+            | Self::Closed(resp)
+            | Self::Dispute(resp)
+            | Self::Unknown(_, resp) => resp,
         }
     }
 }
 
 #[derive(Debug)]
 pub struct StateChannelFollowService {
+    client: GatewayClient,
+    verifier: Arc<PublicKey>,
+    logger: Logger,
     tx: mpsc::Sender<GatewayScFollowReqV1>,
     rx: Streaming,
+    follows: Vec<GatewayScFollowReqV1>,
+    reconnect_policy: RetryPolicy,
+    buffer: usize,
+    metadata: Metadata,
 }
 
+/// The default number of follow requests queued for the validator
+pub const FOLLOW_BUFFER: usize = 3;
+
 impl StateChannelFollowService {
-    pub async fn new(mut client: GatewayClient, verifier: Arc<PublicKey>) -> Result<Self> {
-        let (tx, client_rx) = mpsc::channel(3);
-        let streaming = client
-            .follow_sc(ReceiverStream::new(client_rx))
-            .await?
-            .into_inner();
-        let rx = Streaming {
-            streaming,
+    pub async fn new(
+        client: GatewayClient,
+        verifier: Arc<PublicKey>,
+        logger: Logger,
+    ) -> Result<Self> {
+        Self::with_buffer(client, verifier, logger, FOLLOW_BUFFER).await
+    }
+
+    /// Like `new` but queueing up to buffer follow requests for the
+    /// validator instead of `FOLLOW_BUFFER`.
+    ///
+    /// Once the buffer is full `send` waits until the request stream takes
+    /// a queued request, which happens as fast as the connection allows.
+    /// Senders are slowed down, but no request is dropped. A larger buffer
+    /// lets bursts of follows, and the replay of all follows after a
+    /// reconnect, complete without waiting. A buffer of 0 is taken as 1.
+    pub async fn with_buffer(
+        client: GatewayClient,
+        verifier: Arc<PublicKey>,
+        logger: Logger,
+        buffer: usize,
+    ) -> Result<Self> {
+        Self::open(client, verifier, logger, buffer, Metadata::default()).await
+    }
+
+    /// Like `with_buffer` but sending the given metadata headers with the
+    /// stream, and again with every reopened stream
+    async fn open(
+        mut client: GatewayClient,
+        verifier: Arc<PublicKey>,
+        logger: Logger,
+        buffer: usize,
+        metadata: Metadata,
+    ) -> Result<Self> {
+        let buffer = buffer.max(1);
+        let (tx, rx) =
+            open_follow_stream(&mut client, &verifier, &logger, buffer, &metadata).await?;
+        Ok(Self {
+            client,
             verifier,
-        };
-        Ok(Self { tx, rx })
+            logger,
+            tx,
+            rx,
+            follows: vec![],
+            reconnect_policy: RetryPolicy::default(),
+            buffer,
+            metadata,
+        })
+    }
+
+    /// Use the given policy for reopening the stream after a transient
+    /// error instead of the default one. See `message`.
+    pub fn with_reconnect_policy(self, reconnect_policy: RetryPolicy) -> Self {
+        Self {
+            reconnect_policy,
+            ..self
+        }
     }
 
     pub async fn send(&mut self, id: &[u8], owner: &[u8]) -> Result {
@@ -88,137 +897,2564 @@ impl StateChannelFollowService {
             sc_id: id.into(),
             sc_owner: owner.into(),
         };
+        if !self.follows.contains(&msg) {
+            self.follows.push(msg.clone());
+        }
         Ok(self.tx.send(msg).await?)
     }
 
+    /// Follow all of the given `(id, owner)` state channels on this stream
+    pub async fn follow_many(&mut self, ids_owners: &[(Vec<u8>, Vec<u8>)]) -> Result {
+        for (id, owner) in ids_owners {
+            self.send(id, owner).await?;
+        }
+        Ok(())
+    }
+
+    /// The next update for any of the followed state channels. Updates for
+    /// different state channels are told apart by their `sc_id`.
+    ///
+    /// When the stream fails with a transient error it is reopened, backing
+    /// off according to the reconnect policy, and every state channel
+    /// followed so far is followed again. Once the policy's `max_attempts`
+    /// have failed a `ServiceError::Reconnect` error is returned.
     pub async fn message(&mut self) -> Result<Option<GatewayScFollowStreamedRespV1>> {
         use helium_proto::gateway_resp_v1::Msg;
-        match self.rx.message().await {
-            Ok(Some(Response(GatewayRespV1 {
-                msg: Some(Msg::FollowStreamedResp(resp)),
-                ..
-            }))) => Ok(Some(resp)),
-            Ok(None) => Ok(None),
-            Ok(msg) => Err(Error::custom(format!(
-                "unexpected gateway response {msg:?}",
-            ))),
-            Err(err) => Err(err),
+        loop {
+            match self.rx.message().await {
+                Ok(Some(Response(GatewayRespV1 {
+                    msg: Some(Msg::FollowStreamedResp(resp)),
+                    ..
+                }))) => return Ok(Some(resp)),
+                Ok(None) => return Ok(None),
+                Ok(Some(Response(GatewayRespV1 { msg, .. }))) => {
+                    return Err(GatewayError::unexpected_message("follow", msg))
+                }
+                Err(err) if err.is_transient() => {
+                    debug!(self.logger, "follow stream failed: {err}");
+                    self.reconnect().await?
+                }
+                Err(err) => return Err(err),
+            }
         }
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct GatewayService {
-    pub uri: KeyedUri,
-    client: GatewayClient,
-}
 
-impl GatewayService {
-    pub fn new(keyed_uri: KeyedUri) -> Result<Self> {
-        let channel = Endpoint::from(keyed_uri.uri.clone())
-            .timeout(Duration::from_secs(CONNECT_TIMEOUT))
-            .connect_lazy();
-        Ok(Self {
-            uri: keyed_uri,
-            client: GatewayClient::new(channel),
-        })
+    /// Like `message` but with the update decoded by its close state
+    pub async fn event(&mut self) -> Result<Option<ScFollowEvent>> {
+        Ok(self.message().await?.map(ScFollowEvent::from))
     }
 
-    pub fn random_new(uris: &[KeyedUri]) -> Result<Self> {
-        let uri = uris
-            .choose(&mut OsRng)
-            .ok_or_else(|| Error::custom("empty uri list"))?;
-        Self::new(uri.to_owned())
+    async fn reconnect(&mut self) -> Result {
+        let policy = self.reconnect_policy;
+        let mut backoff = Backoff::new(policy.max_attempts, policy.base_delay, policy.max_delay);
+        backoff.set_jitter(policy.jitter);
+        let mut attempt = 1;
+        loop {
+            let delay = backoff.next(attempt).unwrap_or(policy.max_delay);
+            tokio::time::sleep(delay).await;
+            match self.reopen().await {
+                Ok(()) => {
+                    warn!(self.logger, "follow stream reconnected";
+                        "attempt" => attempt,
+                        "follows" => self.follows.len());
+                    return Ok(());
+                }
+                Err(err) if err.is_transient() && attempt < policy.max_attempts => {
+                    debug!(self.logger, "follow stream reconnect failed: {err}";
+                        "attempt" => attempt);
+                    attempt += 1;
+                }
+                Err(err) if err.is_transient() => return Err(Error::reconnect(attempt)),
+                Err(err) => return Err(err),
+            }
+        }
     }
 
-    pub async fn routing(&mut self, height: u64) -> Result<Streaming> {
-        let stream = self.client.routing(GatewayRoutingReqV1 { height }).await?;
-        Ok(Streaming {
-            streaming: stream.into_inner(),
-            verifier: self.uri.pubkey.clone(),
-        })
+    async fn reopen(&mut self) -> Result {
+        let (tx, rx) = open_follow_stream(
+            &mut self.client,
+            &self.verifier,
+            &self.logger,
+            self.buffer,
+            &self.metadata,
+        )
+        .await?;
+        replay_follows(&tx, &self.follows).await?;
+        self.tx = tx;
+        self.rx = rx;
+        Ok(())
     }
+}
 
-    pub async fn region_params(&mut self, keypair: Arc<Keypair>) -> Result<Streaming> {
-        let mut req = GatewayRegionParamsUpdateReqV1 {
-            address: keypair.public_key().to_vec(),
-            signature: vec![],
-        };
-        req.signature = req.sign(keypair).await?;
+async fn open_follow_stream(
+    client: &mut GatewayClient,
+    verifier: &Arc<PublicKey>,
+    logger: &Logger,
+    buffer: usize,
+    metadata: &Metadata,
+) -> Result<(mpsc::Sender<GatewayScFollowReqV1>, Streaming)> {
+    let (tx, client_rx) = mpsc::channel(buffer);
+    let mut request = tonic::Request::new(ReceiverStream::new(client_rx));
+    add_metadata(metadata, &mut request);
+    let streaming = client.follow_sc(request).await?.into_inner();
+    Ok((
+        tx,
+        Streaming::new(streaming, verifier.clone(), logger.clone()),
+    ))
+}
 
-        let stream = self.client.region_params_update(req).await?;
-        Ok(Streaming {
-            streaming: stream.into_inner(),
-            verifier: self.uri.pubkey.clone(),
-        })
+async fn replay_follows(
+    tx: &mpsc::Sender<GatewayScFollowReqV1>,
+    follows: &[GatewayScFollowReqV1],
+) -> Result {
+    for follow in follows {
+        tx.send(follow.clone()).await?;
     }
+    Ok(())
+}
 
-    pub async fn is_active_sc(
-        &mut self,
-        id: &[u8],
-        owner: &[u8],
-    ) -> Result<GatewayScIsActiveRespV1> {
-        let resp = self
-            .client
-            .is_active_sc(GatewayScIsActiveReqV1 {
-                sc_owner: owner.into(),
-                sc_id: id.into(),
-            })
-            .await?
-            .into_inner();
-        resp.verify(&self.uri.pubkey)?;
-        match resp.msg {
-            Some(gateway_resp_v1::Msg::IsActiveResp(resp)) => {
-                let GatewayScIsActiveRespV1 {
-                    sc_id, sc_owner, ..
-                } = &resp;
-                if sc_id == id && sc_owner == owner {
-                    Ok(resp)
-                } else {
-                    Err(Error::custom("mismatched state channel id and owner"))
+/// A routing stream that survives validator restarts. See
+/// `GatewayService::routing_resilient`.
+#[derive(Debug)]
+pub struct ResilientRouting {
+    service: GatewayService,
+    stream: Option<Streaming>,
+    position: ResumePosition,
+    max_failures: u32,
+}
+
+impl ResilientRouting {
+    /// Returns the next routing response. Transient errors reconnect and
+    /// resume the stream from the last received height. Once `max_failures`
+    /// consecutive attempts have failed a `ServiceError::Reconnect` error is
+    /// returned.
+    pub async fn message(&mut self) -> Result<Option<Response>> {
+        let mut failures = 0;
+        loop {
+            let result = match self.stream.as_mut() {
+                Some(stream) => stream.message().await,
+                None => match self.service.resume_routing(&self.position).await {
+                    Ok(stream) => {
+                        self.stream = Some(stream);
+                        continue;
+                    }
+                    Err(err) => Err(err),
+                },
+            };
+            match result {
+                Ok(Some(response)) => {
+                    if let Some(position) =
+                        self.stream.as_ref().and_then(Streaming::resume_position)
+                    {
+                        self.position = position.clone();
+                    }
+                    return Ok(Some(response));
                 }
+                Ok(None) => return Ok(None),
+                Err(err) if err.is_transient() => {
+                    failures += 1;
+                    if failures >= self.max_failures {
+                        return Err(Error::reconnect(failures));
+                    }
+                    self.stream = None;
+                    self.service = self.service.reconnect();
+                }
+                Err(err) => return Err(err),
             }
-            Some(other) => Err(Error::custom(format!(
-                "invalid is_active response {other:?}",
-            ))),
-            None => Err(Error::custom("empty is_active response")),
         }
     }
 
-    pub async fn follow_sc(&mut self) -> Result<StateChannelFollowService> {
-        StateChannelFollowService::new(self.client.clone(), self.uri.pubkey.clone()).await
+    /// The position the stream will resume from after a reconnect
+    pub fn resume_position(&self) -> &ResumePosition {
+        &self.position
     }
+}
 
-    pub async fn close_sc(&mut self, close_txn: BlockchainTxnStateChannelCloseV1) -> Result {
-        let _ = self
-            .client
-            .close_sc(GatewayScCloseReqV1 {
-                close_txn: Some(close_txn),
-            })
-            .await?;
+/// Serves region params from a cached copy for a limited time before asking
+/// the gateway service again, to avoid re-streaming them on every restart of
+/// a subscription.
+#[derive(Debug)]
+pub struct RegionParamsCache {
+    service: GatewayService,
+    ttl: Duration,
+    cached: Option<(Instant, RegionParams)>,
+}
+
+impl RegionParamsCache {
+    pub fn new(service: GatewayService, ttl: Duration) -> Self {
+        Self {
+            service,
+            ttl,
+            cached: None,
+        }
+    }
+
+    /// The region params, from the cache if they were fetched less than the
+    /// ttl ago, otherwise from the first message of a new region params
+    /// stream.
+    pub async fn region_params(&mut self, keypair: Arc<Keypair>) -> Result<RegionParams> {
+        if let Some((cached_at, params)) = &self.cached {
+            if cached_at.elapsed() < self.ttl {
+                return Ok(params.clone());
+            }
+        }
+        let mut stream = self.service.region_params(keypair).await?;
+        let params = match stream.message().await? {
+            Some(response) => response.region_params()?,
+            None => return Err(GatewayError::empty_response("region_params")),
+        };
+        self.cached = Some((Instant::now(), params.clone()));
+        Ok(params)
+    }
+
+    /// When the cached region params were fetched, if there are any
+    pub fn cached_at(&self) -> Option<Instant> {
+        self.cached.as_ref().map(|(cached_at, _)| *cached_at)
+    }
+}
+
+/// How a `GatewayPool` picks the service for each call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolStrategy {
+    /// The pooled services take turns
+    RoundRobin,
+    /// The service with the fewest calls in flight is used
+    LeastOutstanding,
+}
+
+#[derive(Debug)]
+struct PoolEntry {
+    service: GatewayService,
+    outstanding: Arc<AtomicUsize>,
+    failures: u32,
+}
+
+impl PoolEntry {
+    fn new(service: GatewayService) -> Self {
+        Self {
+            service,
+            outstanding: Arc::new(AtomicUsize::new(0)),
+            failures: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PoolState {
+    entries: Vec<PoolEntry>,
+    next_entry: usize,
+    next_uri: usize,
+}
+
+/// A pooled service handed out for a single call. Counts as outstanding
+/// until dropped.
+#[derive(Debug)]
+struct PoolLease {
+    service: GatewayService,
+    outstanding: Arc<AtomicUsize>,
+}
+
+impl Drop for PoolLease {
+    fn drop(&mut self) {
+        self.outstanding.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A fixed size pool of gateway services, each with its own connection, to
+/// spread concurrent unary calls over several connections rather than
+/// serializing them over one.
+///
+/// A service that fails `max_failures` consecutive calls with transient
+/// errors is dropped and replaced by a service for the next validator in the
+/// list.
+#[derive(Debug)]
+pub struct GatewayPool {
+    uris: Vec<KeyedUri>,
+    strategy: PoolStrategy,
+    max_failures: u32,
+    state: Mutex<PoolState>,
+}
+
+impl GatewayPool {
+    /// Construct a pool of size services, assigned to the given validators in
+    /// turn.
+    pub fn new(
+        uris: &[KeyedUri],
+        size: usize,
+        strategy: PoolStrategy,
+        max_failures: u32,
+    ) -> Result<Self> {
+        if uris.is_empty() {
+            return Err(Error::custom("empty uri list"));
+        }
+        if size == 0 {
+            return Err(Error::custom("empty gateway pool"));
+        }
+        let entries = uris
+            .iter()
+            .cycle()
+            .take(size)
+            .map(|uri| Ok(PoolEntry::new(GatewayService::new(uri.clone())?)))
+            .collect::<Result<Vec<PoolEntry>>>()?;
+        Ok(Self {
+            uris: uris.to_vec(),
+            strategy,
+            max_failures,
+            state: Mutex::new(PoolState {
+                entries,
+                next_entry: 0,
+                next_uri: size % uris.len(),
+            }),
+        })
+    }
+
+    pub fn size(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    pub async fn config(&self, keys: Vec<String>) -> Result<Vec<BlockchainVarV1>> {
+        let mut lease = self.checkout();
+        let result = lease.service.config(keys).await;
+        self.checkin(&lease, &result);
+        result
+    }
+
+    pub async fn height(&self) -> Result<(u64, u64)> {
+        let mut lease = self.checkout();
+        let result = lease.service.height().await;
+        self.checkin(&lease, &result);
+        result
+    }
+
+    pub async fn is_active_sc(&self, id: &[u8], owner: &[u8]) -> Result<GatewayScIsActiveRespV1> {
+        let mut lease = self.checkout();
+        let result = lease.service.is_active_sc(id, owner).await;
+        self.checkin(&lease, &result);
+        result
+    }
+
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        // No code panics while holding the lock, but don't let a panic
+        // elsewhere take the pool down with it
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn checkout(&self) -> PoolLease {
+        let mut state = self.state();
+        let index = match self.strategy {
+            PoolStrategy::RoundRobin => {
+                let index = state.next_entry % state.entries.len();
+                state.next_entry = index + 1;
+                index
+            }
+            PoolStrategy::LeastOutstanding => state
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, entry)| entry.outstanding.load(Ordering::Relaxed))
+                .map(|(index, _)| index)
+                .unwrap_or(0),
+        };
+        let entry = &state.entries[index];
+        entry.outstanding.fetch_add(1, Ordering::Relaxed);
+        PoolLease {
+            service: entry.service.clone(),
+            outstanding: entry.outstanding.clone(),
+        }
+    }
+
+    fn checkin<T>(&self, lease: &PoolLease, result: &Result<T>) {
+        let mut state = self.state();
+        // The service may have been replaced while the call was in flight
+        let index = match state
+            .entries
+            .iter()
+            .position(|entry| Arc::ptr_eq(&entry.outstanding, &lease.outstanding))
+        {
+            Some(index) => index,
+            None => return,
+        };
+        match result {
+            Ok(_) => state.entries[index].failures = 0,
+            Err(err) if err.is_transient() => {
+                state.entries[index].failures += 1;
+                if state.entries[index].failures >= self.max_failures {
+                    let uri = self.uris[state.next_uri].clone();
+                    state.next_uri = (state.next_uri + 1) % self.uris.len();
+                    let service = GatewayService::new(uri)
+                        .unwrap_or_else(|_| state.entries[index].service.reconnect());
+                    state.entries[index] = PoolEntry::new(service);
+                }
+            }
+            Err(_) => (),
+        }
+    }
+}
+
+/// Binary metadata header with the gateway's signature over the encoded
+/// request. See `GatewayService::new_signed`.
+pub const SIGNATURE_HEADER: &str = "x-gateway-signature-bin";
+/// Binary metadata header with the public key of the gateway that signed a
+/// request
+pub const PUBKEY_HEADER: &str = "x-gateway-pubkey-bin";
+
+/// Provides the keypair requests are signed with, looked up for every
+/// request so that key rotations apply to the next one. Only shows the
+/// current public key when debug printed.
+#[derive(Clone)]
+struct RequestSigner(Arc<dyn Fn() -> Arc<Keypair> + Send + Sync>);
+
+impl RequestSigner {
+    fn keypair(&self) -> Arc<Keypair> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RequestSigner")
+            .field(&self.keypair().public_key().to_string())
+            .finish()
+    }
+}
+
+/// Static metadata headers sent with every request of a service
+type Metadata = Arc<Vec<(MetadataKey<Ascii>, MetadataValue<Ascii>)>>;
+
+/// Add static metadata headers to a request
+fn add_metadata<T>(metadata: &Metadata, request: &mut tonic::Request<T>) {
+    let request_metadata = request.metadata_mut();
+    for (key, value) in metadata.iter() {
+        request_metadata.insert(key.clone(), value.clone());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GatewayService {
+    pub uri: KeyedUri,
+    client: GatewayClient,
+    channel: Channel,
+    endpoint: Endpoint,
+    connect_timeout: Duration,
+    rpc_timeout: Duration,
+    keep_alive: KeepAliveConfig,
+    signer: Option<RequestSigner>,
+    metadata: Metadata,
+    call_timeout: Option<Duration>,
+    concurrency: Option<Arc<Semaphore>>,
+    retry_budget: Option<Arc<RetryBudget>>,
+    drain: Arc<Drain>,
+    state: Arc<ConnectionMonitor>,
+}
+
+/// The state of a service's connection, as last observed by its requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// No request has been made on the connection yet
+    Idle,
+    /// A request is under way without the connection having been seen to
+    /// work, or to fail, yet
+    Connecting,
+    /// The validator answered the last request, successfully or with an
+    /// error of its own
+    Ready,
+    /// The last request failed with a transient transport or availability
+    /// error. The next request tries to connect again.
+    TransientFailure,
+}
+
+/// Shares the connection state of a service with its clones and watchers
+#[derive(Debug)]
+struct ConnectionMonitor {
+    tx: watch::Sender<ConnectionState>,
+    // Held so the state can be sent while nobody else watches it
+    rx: watch::Receiver<ConnectionState>,
+}
+
+impl Default for ConnectionMonitor {
+    fn default() -> Self {
+        let (tx, rx) = watch::channel(ConnectionState::Idle);
+        Self { tx, rx }
+    }
+}
+
+impl ConnectionMonitor {
+    fn get(&self) -> ConnectionState {
+        *self.rx.borrow()
+    }
+
+    fn set(&self, state: ConnectionState) {
+        if self.get() != state {
+            // Can't fail while rx is held
+            let _ = self.tx.send(state);
+        }
+    }
+
+    /// Record the state the outcome of a request shows. Errors raised before
+    /// or after the exchange with the validator, like signing or verification
+    /// failures, say nothing about the connection.
+    fn observe<T>(&self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.set(ConnectionState::Ready),
+            Err(err) if err.is_transient() => self.set(ConnectionState::TransientFailure),
+            Err(err) if err.grpc_code().is_some() => self.set(ConnectionState::Ready),
+            Err(_) => (),
+        }
+    }
+}
+
+/// Permits for unary requests in flight. A draining service takes all of
+/// them once its outstanding requests complete.
+const DRAIN_PERMITS: u32 = u32::MAX >> 3;
+
+/// Tracks the unary requests in flight on a service and its clones so they
+/// can be drained on shutdown
+#[derive(Debug)]
+struct Drain {
+    draining: AtomicBool,
+    calls: Arc<Semaphore>,
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self {
+            draining: AtomicBool::new(false),
+            calls: Arc::new(Semaphore::new(DRAIN_PERMITS as usize)),
+        }
+    }
+}
+
+impl Drain {
+    /// Fails if the service is shutting down
+    fn check(&self) -> Result {
+        if self.draining.load(Ordering::SeqCst) {
+            return Err(Error::shutdown());
+        }
         Ok(())
     }
 
-    async fn get_config(&mut self, keys: Vec<String>) -> Result<GatewayRespV1> {
-        let resp = self
-            .client
-            .config(GatewayConfigReqV1 { keys })
+    /// Track a unary request until the returned permit is dropped
+    fn begin(&self) -> Result<OwnedSemaphorePermit> {
+        self.check()?;
+        self.calls
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| Error::shutdown())
+    }
+
+    fn outstanding(&self) -> usize {
+        DRAIN_PERMITS as usize - self.calls.available_permits()
+    }
+}
+
+impl GatewayService {
+    /// Construct a service that connects on its first request.
+    ///
+    /// IPv6 literal hosts like `http://[::1]:8080` are supported. Host names
+    /// resolving to both IPv4 and IPv6 addresses are connected dual-stack: the
+    /// connector races the address families, starting the fallback family
+    /// shortly after the first, and uses whichever connects first.
+    ///
+    /// Host names are resolved each time a connection is established: on the
+    /// first request, and again whenever the connection has dropped and the
+    /// next request reconnects. An established connection is kept, though,
+    /// even if the name has since moved to other addresses. Use `reconnect`
+    /// to move to a fresh resolution right away, for example after a
+    /// validator failover.
+    pub fn new(keyed_uri: KeyedUri) -> Result<Self> {
+        Self::with_timeouts(
+            keyed_uri,
+            Duration::from_secs(CONNECT_TIMEOUT),
+            Duration::from_secs(RPC_TIMEOUT),
+        )
+    }
+
+    /// Construct a service with the given timeouts for establishing the
+    /// connection and for each request, for links where the defaults are too
+    /// short.
+    ///
+    /// With the `tls` feature enabled, `https` uris connect over TLS trusting
+    /// the system's root certificates. Use `new_tls` to trust other roots.
+    pub fn with_timeouts(keyed_uri: KeyedUri, connect: Duration, rpc: Duration) -> Result<Self> {
+        let endpoint = Self::endpoint(&keyed_uri, connect)?;
+        let channel = lazy_channel(&keyed_uri.uri, &endpoint);
+        Ok(Self::from_endpoint(
+            keyed_uri, endpoint, channel, connect, rpc,
+        ))
+    }
+
+    /// Like `new` but establishes the connection up front, failing right away
+    /// if the validator can't be reached instead of on the first request.
+    pub async fn connect(keyed_uri: KeyedUri) -> Result<Self> {
+        let connect = Duration::from_secs(CONNECT_TIMEOUT);
+        let rpc = Duration::from_secs(RPC_TIMEOUT);
+        let endpoint = Self::endpoint(&keyed_uri, connect)?;
+        let channel = eager_channel(&keyed_uri.uri, &endpoint).await?;
+        Ok(Self::from_endpoint(
+            keyed_uri, endpoint, channel, connect, rpc,
+        ))
+    }
+
+    /// Construct a service for a validator listening on the unix socket at
+    /// the given absolute path. The service's uri is `unix://localhost`
+    /// followed by the path, and `new` accepts such uris as well.
+    #[cfg(unix)]
+    pub fn new_uds<P: AsRef<Path>>(path: P, pubkey: Arc<PublicKey>) -> Result<Self> {
+        let path = path.as_ref();
+        let uri = match path.to_str() {
+            Some(path_str) if path.is_absolute() => {
+                format!("{UNIX_SCHEME}://localhost{path_str}").parse()?
+            }
+            _ => return Err(Error::custom("socket path must be absolute utf-8")),
+        };
+        Self::new(KeyedUri { uri, pubkey })
+    }
+
+    fn endpoint(keyed_uri: &KeyedUri, connect: Duration) -> Result<Endpoint> {
+        #[cfg(not(unix))]
+        if is_unix(&keyed_uri.uri) {
+            return Err(Error::custom("unix sockets not supported"));
+        }
+        let endpoint = Self::base_endpoint(keyed_uri, connect);
+        #[cfg(feature = "tls")]
+        let endpoint = if keyed_uri.uri.scheme() == Some(&http::uri::Scheme::HTTPS) {
+            endpoint.tls_config(ClientTlsConfig::new())?
+        } else {
+            endpoint
+        };
+        Ok(endpoint)
+    }
+
+    fn base_endpoint(keyed_uri: &KeyedUri, connect: Duration) -> Endpoint {
+        // Requests over a unix socket still need an http uri for their
+        // headers
+        let uri = if is_unix(&keyed_uri.uri) {
+            Uri::from_static("http://localhost")
+        } else {
+            keyed_uri.uri.clone()
+        };
+        // The rpc timeout is applied per request, so that a call can be given
+        // more time than the default. See `GatewayService::request`.
+        let endpoint = Endpoint::from(uri).connect_timeout(connect);
+        KeepAliveConfig::default().apply(endpoint)
+    }
+
+    /// Construct a service that connects over TLS. The given config carries
+    /// the CA roots to trust and, optionally, a client identity to present.
+    #[cfg(feature = "tls")]
+    pub fn new_tls(keyed_uri: KeyedUri, tls_config: ClientTlsConfig) -> Result<Self> {
+        let connect = Duration::from_secs(CONNECT_TIMEOUT);
+        let rpc = Duration::from_secs(RPC_TIMEOUT);
+        let endpoint = Self::base_endpoint(&keyed_uri, connect).tls_config(tls_config)?;
+        let channel = lazy_channel(&keyed_uri.uri, &endpoint);
+        Ok(Self::from_endpoint(
+            keyed_uri, endpoint, channel, connect, rpc,
+        ))
+    }
+
+    fn from_endpoint(
+        keyed_uri: KeyedUri,
+        endpoint: Endpoint,
+        channel: Channel,
+        connect: Duration,
+        rpc: Duration,
+    ) -> Self {
+        Self {
+            uri: keyed_uri,
+            client: new_client(channel.clone()),
+            channel,
+            endpoint,
+            connect_timeout: connect,
+            rpc_timeout: rpc,
+            keep_alive: KeepAliveConfig::default(),
+            signer: None,
+            metadata: Arc::new(vec![]),
+            call_timeout: None,
+            concurrency: None,
+            retry_budget: None,
+            drain: Arc::new(Drain::default()),
+            state: Arc::new(ConnectionMonitor::default()),
+        }
+    }
+
+    /// This service with at most max unary requests in flight at a time,
+    /// shared with its clones. Further requests wait for one of those to
+    /// complete. Streams are not limited.
+    pub fn with_max_concurrency(self, max: usize) -> Self {
+        Self {
+            concurrency: Some(Arc::new(Semaphore::new(max))),
+            ..self
+        }
+    }
+
+    /// This service with the retries of its `*_with_retry` requests limited
+    /// by the given budget, shared with its clones.
+    pub fn with_retry_budget(self, budget: RetryBudget) -> Self {
+        Self {
+            retry_budget: Some(Arc::new(budget)),
+            ..self
+        }
+    }
+
+    /// Wait for a turn to send a unary request if concurrency is limited
+    async fn permit(&self) -> Option<OwnedSemaphorePermit> {
+        match &self.concurrency {
+            // The semaphore is never closed
+            Some(semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        }
+    }
+
+    /// Stop this service and its clones from sending new requests, wait up to
+    /// grace for unary requests already in flight to complete, and then drop
+    /// this service's connection.
+    ///
+    /// Requests started after shutdown begins fail with
+    /// `ServiceError::Shutdown`, as do new streams. Streams already open are
+    /// not waited for. The connection closes once the clones of this service
+    /// and the streams opened through them are dropped as well.
+    pub async fn shutdown(self, grace: Duration) {
+        let logger = self.logger("shutdown");
+        self.drain.draining.store(true, Ordering::SeqCst);
+        let drained = tokio::time::timeout(
+            grace,
+            self.drain.calls.clone().acquire_many_owned(DRAIN_PERMITS),
+        )
+        .await;
+        if drained.is_err() {
+            warn!(
+                logger,
+                "abandoning {} requests after {grace:?}",
+                self.drain.outstanding()
+            );
+        }
+        self.drain.calls.close();
+        debug!(logger, "shut down");
+    }
+
+    /// Construct a service that signs its requests with the given keypair,
+    /// for validators that authenticate gateways.
+    ///
+    /// The `routing`, `config`, `height`, `time_skew`, `is_active_sc` and
+    /// `close_sc` requests carry the gateway's signature over the encoded
+    /// request in the `SIGNATURE_HEADER` metadata header, and its public key
+    /// in `PUBKEY_HEADER`. `region_params` requests are signed in their body
+    /// either way. `follow_sc` requests are a stream of messages and stay
+    /// unsigned.
+    pub fn new_signed(keyed_uri: KeyedUri, keypair: Arc<Keypair>) -> Result<Self> {
+        Self::new_signed_with(keyed_uri, move || keypair.clone())
+    }
+
+    /// Like `new_signed` but with the keypair looked up through the given
+    /// function for every request, for gateways that rotate their keys. A
+    /// rotation applies from the next request on, without rebuilding the
+    /// service.
+    pub fn new_signed_with<F>(keyed_uri: KeyedUri, keypair_fn: F) -> Result<Self>
+    where
+        F: Fn() -> Arc<Keypair> + Send + Sync + 'static,
+    {
+        let mut service = Self::new(keyed_uri)?;
+        service.signer = Some(RequestSigner(Arc::new(keypair_fn)));
+        Ok(service)
+    }
+
+    /// This service with the given static metadata headers added to every
+    /// request, for proxies in front of the validator that route on them.
+    /// Header names must be lowercase ascii and values visible ascii.
+    ///
+    /// The headers are sent alongside the signature headers of a signing
+    /// service, and when opening `follow_sc` streams. Binary headers, whose
+    /// names end in `-bin`, are not supported.
+    pub fn with_metadata(self, headers: HashMap<String, String>) -> Result<Self> {
+        let metadata = headers
+            .into_iter()
+            .map(|(key, value)| {
+                let invalid = || Error::custom(format!("invalid metadata header {key}"));
+                // tonic panics on binary header names for ascii keys
+                if key.ends_with("-bin") {
+                    return Err(invalid());
+                }
+                let key = MetadataKey::from_bytes(key.as_bytes()).map_err(|_| invalid())?;
+                let value = MetadataValue::from_str(&value).map_err(|_| invalid())?;
+                Ok((key, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            metadata: Arc::new(metadata),
+            ..self
+        })
+    }
+
+    /// Wrap a unary message in a request, signed if this service signs
+    /// requests. The request carries the per-call timeout, or `rpc_timeout`
+    /// by default, to the validator.
+    async fn request<T: Message>(&self, msg: T) -> Result<tonic::Request<T>> {
+        let mut request = self.stream_request(msg).await?;
+        request.set_timeout(self.unary_timeout());
+        Ok(request)
+    }
+
+    /// Like `request` but without a timeout, for requests opening a stream
+    /// that stays open well past any rpc timeout
+    async fn stream_request<T: Message>(&self, msg: T) -> Result<tonic::Request<T>> {
+        if self.state.get() != ConnectionState::Ready {
+            self.state.set(ConnectionState::Connecting);
+        }
+        let mut request = tonic::Request::new(msg);
+        add_metadata(&self.metadata, &mut request);
+        if let Some(signer) = &self.signer {
+            let keypair = signer.keypair();
+            let signature = sign_encoded(request.get_ref(), keypair.clone()).await?;
+            let metadata = request.metadata_mut();
+            metadata.insert_bin(SIGNATURE_HEADER, MetadataValue::from_bytes(&signature));
+            metadata.insert_bin(
+                PUBKEY_HEADER,
+                MetadataValue::from_bytes(&keypair.public_key().to_vec()),
+            );
+        }
+        Ok(request)
+    }
+
+    /// This service with the given keep-alive settings instead of the
+    /// defaults. Any connection already established is replaced by a new one
+    /// opened on the next request.
+    pub fn with_keep_alive(self, keep_alive: KeepAliveConfig) -> Self {
+        let endpoint = keep_alive.apply(self.endpoint);
+        let channel = lazy_channel(&self.uri.uri, &endpoint);
+        Self {
+            client: new_client(channel.clone()),
+            channel,
+            endpoint,
+            keep_alive,
+            state: Arc::new(ConnectionMonitor::default()),
+            ..self
+        }
+    }
+
+    /// A new service for the same validator with the same settings but its
+    /// own connection. The connection is established on its first request,
+    /// resolving the validator's host name anew, and doesn't reuse this
+    /// service's connection. `routing_resilient` streams and `GatewayPool`
+    /// use this to replace failing connections.
+    pub fn reconnect(&self) -> Self {
+        let channel = lazy_channel(&self.uri.uri, &self.endpoint);
+        Self {
+            client: new_client(channel.clone()),
+            channel,
+            state: Arc::new(ConnectionMonitor::default()),
+            ..self.clone()
+        }
+    }
+
+    /// The state of this service's connection as of its last request, shared
+    /// with its clones. Services returned by `reconnect` and
+    /// `with_keep_alive` start out `Idle` on their new connection.
+    ///
+    /// The state is updated as requests complete rather than by probing the
+    /// connection, so a connection that drops while idle still reads `Ready`
+    /// until the next request.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.state.get()
+    }
+
+    /// A receiver notified of every change of `connection_state`
+    pub fn watch_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.tx.subscribe()
+    }
+
+    /// Record what the outcome of a request shows about the connection and
+    /// log it
+    fn finish<T>(&self, logger: &Logger, result: Result<T>) -> Result<T> {
+        self.state.observe(&result);
+        logged(logger, result)
+    }
+
+    /// The channel this service's requests go over, to build clients for
+    /// other services on the same connection. Requests on the channel share
+    /// this service's connect timeout and keep-alive settings. The rpc
+    /// timeout is applied by this service per request and doesn't carry
+    /// over.
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    pub fn rpc_timeout(&self) -> Duration {
+        self.rpc_timeout
+    }
+
+    pub fn keep_alive(&self) -> KeepAliveConfig {
+        self.keep_alive
+    }
+
+    /// A logger for a request to this service's validator, under the global
+    /// logger
+    fn logger(&self, rpc: &'static str) -> Logger {
+        slog_scope::logger().new(o!(
+            "module" => "gateway_service",
+            "uri" => self.uri.uri.to_string(),
+            "rpc" => rpc,
+        ))
+    }
+
+    /// Connect to one of the given gateways, trying them in random order
+    /// until one responds to a height request. Fails with the error of the
+    /// last gateway tried if none of them respond.
+    pub async fn random_new(uris: &[KeyedUri]) -> Result<Self> {
+        if uris.is_empty() {
+            return Err(Error::custom("empty uri list"));
+        }
+        let mut uris = uris.to_vec();
+        uris.shuffle(&mut OsRng);
+        Self::first_responding(uris, Self::probe_height).await
+    }
+
+    /// Like `random_new` but tries the gateways in a random order weighted by
+    /// the given function, for example by validator stake. Each next gateway
+    /// is picked with a probability proportional to its weight among the
+    /// gateways not yet tried. Equal weights give a uniform order; negative
+    /// weights count as zero.
+    pub async fn weighted_new<F>(uris: &[KeyedUri], weight_fn: F) -> Result<Self>
+    where
+        F: Fn(&KeyedUri) -> f64,
+    {
+        if uris.is_empty() {
+            return Err(Error::custom("empty uri list"));
+        }
+        let uris = weighted_order(uris, weight_fn, &mut OsRng);
+        Self::first_responding(uris, Self::probe_height).await
+    }
+
+    /// A service for the first of the given gateways, in order, that passes
+    /// the given probe. Fails with the error of the last gateway tried if
+    /// none of them do.
+    async fn first_responding<P, Fut>(uris: Vec<KeyedUri>, probe: P) -> Result<Self>
+    where
+        P: Fn(Self) -> Fut,
+        Fut: Future<Output = Result<Self>>,
+    {
+        let mut last_err = None;
+        for uri in uris {
+            match probe(Self::new(uri)?).await {
+                Ok(service) => return Ok(service),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| Error::custom("empty uri list")))
+    }
+
+    /// Check that the validator responds to a height request within the
+    /// connect timeout rather than the longer rpc timeout, so that a dead
+    /// gateway doesn't hold up trying the next one
+    async fn probe_height(mut self) -> Result<Self> {
+        let timeout = self.connect_timeout;
+        self.height_with_timeout(timeout).await?;
+        Ok(self)
+    }
+
+    /// Connect to the given gateway that answers a `ping` the fastest,
+    /// probing all of them concurrently. Falls back to `random_new`
+    /// if none of them respond.
+    pub async fn fastest_new(uris: &[KeyedUri]) -> Result<Self> {
+        let probes = uris
+            .iter()
+            .map(|uri| async move {
+                let mut service = Self::new(uri.clone())?;
+                let rtt = service.ping().await?;
+                Ok((service, rtt))
+            })
+            .collect::<Vec<_>>();
+        let probes: Vec<Result<(Self, Duration)>> = futures::future::join_all(probes).await;
+        match fastest(probes) {
+            Some(service) => Ok(service),
+            None => Self::random_new(uris).await,
+        }
+    }
+
+    pub async fn routing(&mut self, height: u64) -> Result<Streaming> {
+        self.resume_routing(&ResumePosition::Height(height)).await
+    }
+
+    /// Like `routing` but yields the routings of the streamed responses one
+    /// at a time. Each response is still verified as a whole before its
+    /// routings are yielded.
+    pub async fn routing_flat(
+        &mut self,
+        height: u64,
+    ) -> Result<impl Stream<Item = Result<Routing>>> {
+        Ok(flat_routings(self.routing(height).await?))
+    }
+
+    /// A routing stream starting at the given height that ends when the
+    /// given shutdown listener is triggered. See `Streaming::with_shutdown`.
+    pub async fn routing_with_cancel(
+        &mut self,
+        height: u64,
+        shutdown: triggered::Listener,
+    ) -> Result<Streaming> {
+        Ok(self.routing(height).await?.with_shutdown(shutdown))
+    }
+
+    /// A routing stream starting at the given height that reconnects to the
+    /// validator on transient errors, resuming from the last received height.
+    /// The stream is opened on the first call to `message`, and only gives
+    /// up after `max_failures` consecutive failed attempts.
+    pub fn routing_resilient(&self, height: u64, max_failures: u32) -> ResilientRouting {
+        ResilientRouting {
+            service: self.clone(),
+            stream: None,
+            position: ResumePosition::Height(height),
+            max_failures,
+        }
+    }
+
+    /// Open a routing stream starting at the given resume position, usually
+    /// taken from the `resume_position` of a previous stream.
+    pub async fn resume_routing(&mut self, position: &ResumePosition) -> Result<Streaming> {
+        let logger = self.logger("routing");
+        let result = async {
+            self.drain.check()?;
+            let height = position.height()?;
+            let request = self.stream_request(GatewayRoutingReqV1 { height }).await?;
+            let stream = self.client.routing(request).await?;
+            Ok(Streaming::new(
+                stream.into_inner(),
+                self.uri.pubkey.clone(),
+                logger.clone(),
+            ))
+        }
+        .await;
+        self.finish(&logger, result)
+    }
+
+    /// A region params stream signed with this service's current signing
+    /// keypair. Fails for services that don't sign requests; see
+    /// `new_signed_with`.
+    pub async fn signed_region_params(&mut self) -> Result<Streaming> {
+        let keypair = match &self.signer {
+            Some(signer) => signer.keypair(),
+            None => return Err(Error::custom("service does not sign requests")),
+        };
+        self.region_params(keypair).await
+    }
+
+    pub async fn region_params(&mut self, keypair: Arc<Keypair>) -> Result<Streaming> {
+        let logger = self.logger("region_params");
+        let result = async {
+            self.drain.check()?;
+            let mut req = GatewayRegionParamsUpdateReqV1 {
+                address: keypair.public_key().to_vec(),
+                signature: vec![],
+            };
+            req.signature = req.sign(keypair).await?;
+
+            let mut request = tonic::Request::new(req);
+            add_metadata(&self.metadata, &mut request);
+            let stream = self.client.region_params_update(request).await?;
+            Ok(Streaming::new(
+                stream.into_inner(),
+                self.uri.pubkey.clone(),
+                logger.clone(),
+            ))
+        }
+        .await;
+        self.finish(&logger, result)
+    }
+
+    pub async fn is_active_sc(
+        &mut self,
+        id: &[u8],
+        owner: &[u8],
+    ) -> Result<GatewayScIsActiveRespV1> {
+        let logger = self.logger("is_active_sc");
+        let result = async {
+            let _call = self.drain.begin()?;
+            let _permit = self.permit().await;
+            let request = self
+                .request(GatewayScIsActiveReqV1 {
+                    sc_owner: owner.into(),
+                    sc_id: id.into(),
+                })
+                .await?;
+            let timeout = self.unary_timeout();
+            let resp = deadline(&logger, timeout, async {
+                Ok(self.client.is_active_sc(request).await?)
+            })
             .await?
             .into_inner();
-        resp.verify(&self.uri.pubkey)?;
+            verify_response(&logger, &resp, &self.uri.pubkey)?;
+            match resp.msg {
+                Some(gateway_resp_v1::Msg::IsActiveResp(resp)) => {
+                    let GatewayScIsActiveRespV1 {
+                        sc_id, sc_owner, ..
+                    } = &resp;
+                    if sc_id == id && sc_owner == owner {
+                        Ok(resp)
+                    } else {
+                        Err(Error::custom("mismatched state channel id and owner"))
+                    }
+                }
+                Some(other) => Err(GatewayError::unexpected_message("is_active", other)),
+                None => Err(GatewayError::empty_response("is_active")),
+            }
+        }
+        .await;
+        self.finish(&logger, result)
+    }
+
+    /// `is_active_sc`, retried according to the given policy
+    pub async fn is_active_sc_with_retry(
+        &mut self,
+        id: &[u8],
+        owner: &[u8],
+        policy: &RetryPolicy,
+    ) -> Result<GatewayScIsActiveRespV1> {
+        policy
+            .retry(
+                &self.logger("is_active_sc"),
+                self.retry_budget.as_deref(),
+                || {
+                    let mut service = self.clone();
+                    async move { service.is_active_sc(id, owner).await }
+                },
+            )
+            .await
+    }
+
+    pub async fn follow_sc(&mut self) -> Result<StateChannelFollowService> {
+        self.follow_sc_with_buffer(FOLLOW_BUFFER).await
+    }
+
+    /// Like `follow_sc` but queueing up to buffer follow requests. See
+    /// `StateChannelFollowService::with_buffer`.
+    pub async fn follow_sc_with_buffer(
+        &mut self,
+        buffer: usize,
+    ) -> Result<StateChannelFollowService> {
+        let logger = self.logger("follow_sc");
+        if let Err(err) = self.drain.check() {
+            return logged(&logger, Err(err));
+        }
+        let result = StateChannelFollowService::open(
+            self.client.clone(),
+            self.uri.pubkey.clone(),
+            logger.clone(),
+            buffer,
+            self.metadata.clone(),
+        )
+        .await;
+        self.finish(&logger, result)
+    }
+
+    /// Submit a state channel close transaction, returning the validator's
+    /// verified acknowledgement of it.
+    pub async fn close_sc(
+        &mut self,
+        close_txn: BlockchainTxnStateChannelCloseV1,
+    ) -> Result<GatewayScCloseRespV1> {
+        let logger = self.logger("close_sc");
+        let result = async {
+            let id = close_txn
+                .state_channel
+                .as_ref()
+                .map(|sc| sc.id.clone())
+                .unwrap_or_default();
+            let _call = self.drain.begin()?;
+            let _permit = self.permit().await;
+            let request = self
+                .request(GatewayScCloseReqV1 {
+                    close_txn: Some(close_txn),
+                })
+                .await?;
+            let timeout = self.unary_timeout();
+            let resp = deadline(&logger, timeout, async {
+                Ok(self.client.close_sc(request).await?)
+            })
+            .await?
+            .into_inner();
+            verify_response(&logger, &resp, &self.uri.pubkey)?;
+            match resp.msg {
+                Some(gateway_resp_v1::Msg::CloseResp(resp)) => {
+                    if resp.sc_id == id {
+                        Ok(resp)
+                    } else {
+                        Err(Error::custom("mismatched state channel id"))
+                    }
+                }
+                Some(other) => Err(GatewayError::unexpected_message("close", other)),
+                None => Err(GatewayError::empty_response("close")),
+            }
+        }
+        .await;
+        self.finish(&logger, result)
+    }
+
+    async fn get_config(&mut self, logger: &Logger, keys: Vec<String>) -> Result<GatewayRespV1> {
+        let _call = self.drain.begin()?;
+        let _permit = self.permit().await;
+        let request = self.request(GatewayConfigReqV1 { keys }).await?;
+        let timeout = self.unary_timeout();
+        let resp = deadline(logger, timeout, async {
+            Ok(self.client.config(request).await?)
+        })
+        .await?
+        .into_inner();
+        verify_response(logger, &resp, &self.uri.pubkey)?;
         Ok(resp)
     }
 
     pub async fn config(&mut self, keys: Vec<String>) -> Result<Vec<BlockchainVarV1>> {
-        match self.get_config(keys).await?.msg {
-            Some(gateway_resp_v1::Msg::ConfigResp(GatewayConfigRespV1 { result })) => Ok(result),
-            Some(other) => Err(Error::custom(format!("invalid config response {other:?}"))),
-            None => Err(Error::custom("empty config response")),
+        let logger = self.logger("config");
+        let result = async {
+            match self.get_config(&logger, keys).await?.msg {
+                Some(gateway_resp_v1::Msg::ConfigResp(GatewayConfigRespV1 { result })) => {
+                    Ok(result)
+                }
+                Some(other) => Err(GatewayError::unexpected_message("config", other)),
+                None => Err(GatewayError::empty_response("config")),
+            }
+        }
+        .await;
+        self.finish(&logger, result)
+    }
+
+    /// `config` with its own timeout in place of this service's
+    /// `rpc_timeout`. The timeout may be longer than `rpc_timeout`, for
+    /// calls known to be slow, or shorter, for cheap calls that should fail
+    /// fast, without changing it for other calls.
+    pub async fn config_with_timeout(
+        &mut self,
+        keys: Vec<String>,
+        timeout: Duration,
+    ) -> Result<Vec<BlockchainVarV1>> {
+        let mut service = self.with_call_timeout(timeout);
+        let logger = self.logger("config");
+        deadline(&logger, timeout, service.config(keys)).await
+    }
+
+    /// `height` with its own timeout. See `config_with_timeout`.
+    pub async fn height_with_timeout(&mut self, timeout: Duration) -> Result<(u64, u64)> {
+        let mut service = self.with_call_timeout(timeout);
+        let logger = self.logger("height");
+        deadline(&logger, timeout, service.height()).await
+    }
+
+    /// The timeout of a unary request: the per-call timeout if one is set,
+    /// `rpc_timeout` otherwise
+    fn unary_timeout(&self) -> Duration {
+        self.call_timeout.unwrap_or(self.rpc_timeout)
+    }
+
+    /// A clone of this service whose unary requests carry the given timeout
+    /// to the validator
+    fn with_call_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            call_timeout: Some(timeout),
+            ..self.clone()
         }
     }
 
+    /// `config`, retried according to the given policy
+    pub async fn config_with_retry(
+        &mut self,
+        keys: Vec<String>,
+        policy: &RetryPolicy,
+    ) -> Result<Vec<BlockchainVarV1>> {
+        policy
+            .retry(&self.logger("config"), self.retry_budget.as_deref(), || {
+                let mut service = self.clone();
+                let keys = keys.clone();
+                async move { service.config(keys).await }
+            })
+            .await
+    }
+
+    /// Like `config` but keyed by variable name. Fails with
+    /// `GatewayError::MissingKeys` listing any requested keys the validator
+    /// did not return a variable for.
+    pub async fn config_map(
+        &mut self,
+        keys: Vec<String>,
+    ) -> Result<HashMap<String, BlockchainVarV1>> {
+        let vars = self.config(keys.clone()).await?;
+        config_map(&keys, vars)
+    }
+
+    /// Like `config` but with each variable decoded according to its type,
+    /// keyed by variable name
+    pub async fn config_typed(
+        &mut self,
+        keys: Vec<String>,
+    ) -> Result<HashMap<String, ConfigValue>> {
+        self.config(keys)
+            .await?
+            .iter()
+            .map(|var| Ok((var.name.clone(), ConfigValue::try_from(var)?)))
+            .collect()
+    }
+
+    /// Fetch the well-known state channel chain variables
+    pub async fn chain_vars(&mut self) -> Result<ChainVars> {
+        ChainVars::try_from(self.config(ChainVars::keys()).await?)
+    }
+
     pub async fn height(&mut self) -> Result<(u64, u64)> {
-        let resp = self.get_config(vec![]).await?;
-        Ok((resp.height, resp.block_age))
+        let logger = self.logger("height");
+        let result = self
+            .get_config(&logger, vec![])
+            .await
+            .map(|resp| (resp.height, resp.block_age));
+        self.finish(&logger, result)
+    }
+
+    /// Whether the validator's last block is at most max_block_age seconds
+    /// old
+    pub async fn is_synced(&mut self, max_block_age: u64) -> Result<bool> {
+        let (_, block_age) = self.height().await?;
+        Ok(block_age <= max_block_age)
+    }
+
+    /// Like `height` but fails with `ServiceError::StaleValidator` if the
+    /// validator's last block is more than max_block_age seconds old
+    pub async fn height_checked(&mut self, max_block_age: u64) -> Result<(u64, u64)> {
+        let (height, block_age) = self.height().await?;
+        if block_age > max_block_age {
+            return Err(Error::stale_validator(block_age, max_block_age));
+        }
+        Ok((height, block_age))
+    }
+
+    /// Check that the validator responds, returning the round trip time of
+    /// the smallest request it serves, a config request for no keys.
+    ///
+    /// A validator that can't be reached fails with a transient error (see
+    /// `Error::is_transient`), distinct from a response that fails signature
+    /// verification.
+    pub async fn ping(&mut self) -> Result<Duration> {
+        let logger = self.logger("ping");
+        let start = Instant::now();
+        let result = self
+            .get_config(&logger, vec![])
+            .await
+            .map(|_| start.elapsed());
+        self.finish(&logger, result)
+    }
+
+    /// `height`, retried according to the given policy
+    pub async fn height_with_retry(&mut self, policy: &RetryPolicy) -> Result<(u64, u64)> {
+        policy
+            .retry(&self.logger("height"), self.retry_budget.as_deref(), || {
+                let mut service = self.clone();
+                async move { service.height().await }
+            })
+            .await
+    }
+
+    /// Estimate how far the local clock is off from the validator's clock.
+    ///
+    /// The validator's notion of "now" is taken to be the `block_time` of
+    /// the response plus its `block_age`, and is compared against the local
+    /// time halfway through the request round trip. The estimate is only as
+    /// good as the validator's own clock, has a resolution of one second since
+    /// both values are reported in whole seconds, and assumes the round trip
+    /// is symmetric.
+    pub async fn time_skew(&mut self) -> Result<TimeSkew> {
+        let logger = self.logger("time_skew");
+        let sent = SystemTime::now();
+        let start = Instant::now();
+        let resp = self.finish(&logger, self.get_config(&logger, vec![]).await)?;
+        let rtt = start.elapsed();
+        let local = (sent + rtt / 2).duration_since(UNIX_EPOCH)?;
+        let remote = Duration::from_secs(resp.block_time + resp.block_age);
+        let skew = if local >= remote {
+            TimeSkew::Ahead(local - remote)
+        } else {
+            TimeSkew::Behind(remote - local)
+        };
+        Ok(skew)
+    }
+}
+
+/// The devaddr ranges covered by the subnets of a routing, using netid_list
+/// to translate subnet addresses to devaddrs. Each subnet gives one range for
+/// every NetID it overlaps.
+pub fn routing_devaddr_ranges(routing: &Routing, netid_list: &[NetId]) -> Vec<DevAddrRange> {
+    routing
+        .subnets
+        .iter()
+        .flat_map(|subnet| DevAddrFilter::from_bin(subnet).devaddr_ranges(netid_list))
+        .collect()
+}
+
+/// The service with the shortest round trip among the successful probes
+fn fastest(probes: Vec<Result<(GatewayService, Duration)>>) -> Option<GatewayService> {
+    probes
+        .into_iter()
+        .filter_map(Result::ok)
+        .min_by_key(|(_, rtt)| *rtt)
+        .map(|(service, _)| service)
+}
+
+fn extend_distinct<T: Clone + PartialEq>(target: &mut Vec<T>, items: &[T]) {
+    for item in items {
+        if !target.contains(item) {
+            target.push(item.clone());
+        }
+    }
+}
+
+/// The subnets not covered by another subnet in the list. Of identical
+/// subnets only the first is kept.
+fn collapse_subnets(subnets: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    let filters: Vec<DevAddrFilter> = subnets.iter().map(DevAddrFilter::from_bin).collect();
+    let redundant = |index: usize| {
+        let filter = &filters[index];
+        filters.iter().enumerate().any(|(other_index, other)| {
+            other_index != index
+                && other.covers(filter)
+                && (other_index < index || !filter.covers(other))
+        })
+    };
+    subnets
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !redundant(*index))
+        .map(|(_, subnet)| subnet.clone())
+        .collect()
+}
+
+fn config_map(
+    keys: &[String],
+    vars: Vec<BlockchainVarV1>,
+) -> Result<HashMap<String, BlockchainVarV1>> {
+    let map: HashMap<String, BlockchainVarV1> = vars
+        .into_iter()
+        .map(|var| (var.name.clone(), var))
+        .collect();
+    let missing: Vec<String> = keys
+        .iter()
+        .filter(|key| !map.contains_key(*key))
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        return Err(GatewayError::missing_keys(missing));
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed_uri(uri: &'static str) -> KeyedUri {
+        KeyedUri {
+            uri: Uri::from_static(uri),
+            pubkey: Arc::new(
+                "11tk4zzbyfMPYYHYda255ACoqfYFVdrUSoCWrCYfn8BoyuYrERK"
+                    .parse()
+                    .expect("pubkey"),
+            ),
+        }
+    }
+
+    #[test]
+    fn region_code() {
+        use helium_proto::GatewayRegionParamsStreamedRespV1;
+        let response = Response(GatewayRespV1 {
+            msg: Some(gateway_resp_v1::Msg::RegionParamsStreamedResp(
+                GatewayRegionParamsStreamedRespV1 {
+                    region: 9999,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        });
+        assert_eq!(9999, response.region_code().expect("region code"));
+        assert!(matches!(
+            response.region(),
+            Err(Error::Decode(crate::error::DecodeError::UnknownRegion(
+                9999
+            )))
+        ));
+        assert!(Response(GatewayRespV1::default()).region_code().is_err());
+    }
+
+    #[tokio::test]
+    async fn region_params_cache() {
+        use helium_crypto::{KeyTag, KeyType, Network};
+        let keypair = Arc::new(Keypair::generate(
+            KeyTag {
+                network: Network::MainNet,
+                key_type: KeyType::Ed25519,
+            },
+            &mut OsRng,
+        ));
+        // Nothing listens here, so any request to the service fails
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:1")).expect("service");
+        let mut cache = RegionParamsCache::new(service, Duration::from_secs(60));
+        let cached_at = Instant::now();
+        cache.cached = Some((
+            cached_at,
+            RegionParams {
+                region: Region::from_i32(helium_proto::Region::Us915.into()).expect("region"),
+                channels: vec![],
+            },
+        ));
+        for _ in 0..2 {
+            let params = cache.region_params(keypair.clone()).await.expect("cached");
+            assert_eq!("US915", params.region.to_string());
+        }
+        assert_eq!(Some(cached_at), cache.cached_at());
+
+        cache.ttl = Duration::ZERO;
+        assert!(cache.region_params(keypair).await.is_err());
+    }
+
+    #[test]
+    fn response_block_age() {
+        let response = Response(GatewayRespV1 {
+            height: 1000,
+            block_age: 42,
+            ..Default::default()
+        });
+        assert_eq!(1000, response.height());
+        assert_eq!(42, response.block_age());
+    }
+
+    #[test]
+    fn config_values() {
+        let var = |name: &str, r#type: &str, value: &[u8]| BlockchainVarV1 {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            value: value.to_vec(),
+        };
+        let decode = |var: BlockchainVarV1| ConfigValue::try_from(&var).expect("config value");
+        assert_eq!(
+            ConfigValue::Int(10),
+            decode(var("sc_grace_blocks", "int", b"10"))
+        );
+        assert_eq!(
+            ConfigValue::Float(0.25),
+            decode(var("dc_percent", "float", b"0.25"))
+        );
+        assert_eq!(
+            ConfigValue::Atom("true".to_string()),
+            decode(var("txn_fees", "atom", b"true"))
+        );
+        assert_eq!(
+            ConfigValue::Binary(vec![0, 1, 2]),
+            decode(var("price_oracle_public_keys", "binary", &[0, 1, 2]))
+        );
+        assert!(ConfigValue::try_from(&var("sc_grace_blocks", "int", b"ten")).is_err());
+        assert!(ConfigValue::try_from(&var("unknown", "list", b"")).is_err());
+    }
+
+    #[test]
+    fn chain_vars() {
+        let var = |name: &str, r#type: &str, value: &[u8]| BlockchainVarV1 {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            value: value.to_vec(),
+        };
+        let vars = vec![
+            var("sc_version", "int", b"2"),
+            var("sc_grace_blocks", "int", b"10"),
+            var("dc_payload_size", "int", b"24"),
+            var("max_open_sc", "int", b"5"),
+            var("txn_fees", "atom", b"true"),
+        ];
+        assert_eq!(
+            ChainVars {
+                sc_version: 2,
+                sc_grace_blocks: 10,
+                dc_payload_size: 24,
+                max_open_sc: 5,
+                ..Default::default()
+            },
+            ChainVars::try_from(vars).expect("chain vars")
+        );
+        assert!(ChainVars::try_from(vec![var("sc_grace_blocks", "int", b"-1")]).is_err());
+        assert!(ChainVars::try_from(vec![var("sc_grace_blocks", "atom", b"ten")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn flat_routings() {
+        use helium_proto::GatewayRoutingStreamedRespV1;
+        let routing = |oui: u32| Routing {
+            oui,
+            ..Default::default()
+        };
+        let response = |ouis: [u32; 2]| {
+            Ok(Response(GatewayRespV1 {
+                msg: Some(gateway_resp_v1::Msg::RoutingStreamedResp(
+                    GatewayRoutingStreamedRespV1 {
+                        routings: ouis.map(routing).to_vec(),
+                    },
+                )),
+                ..Default::default()
+            }))
+        };
+        let stream = futures::stream::iter(vec![response([1, 2]), response([3, 4])]);
+        let routings: Vec<Routing> = super::flat_routings(stream)
+            .map(|routing| routing.expect("routing"))
+            .collect()
+            .await;
+        assert_eq!(
+            vec![routing(1), routing(2), routing(3), routing(4)],
+            routings
+        );
+
+        let stream = futures::stream::iter(vec![Ok(Response(GatewayRespV1::default()))]);
+        let routings: Vec<Result<Routing>> = super::flat_routings(stream).collect().await;
+        assert_eq!(1, routings.len());
+        assert!(routings[0].is_err());
+    }
+
+    #[test]
+    fn deduped_routings() {
+        use helium_proto::{GatewayRoutingStreamedRespV1, RoutingAddress};
+        let address = RoutingAddress {
+            pub_key: vec![1],
+            uri: b"http://127.0.0.1:8080".to_vec(),
+        };
+        let routing = |oui: u32, subnets: Vec<Vec<u8>>| Routing {
+            oui,
+            addresses: vec![address.clone()],
+            subnets,
+            ..Default::default()
+        };
+        let subnet = vec![0, 4, 0, 127, 255, 240];
+        let nested = vec![0, 4, 4, 127, 255, 254];
+        let response = Response(GatewayRespV1 {
+            msg: Some(gateway_resp_v1::Msg::RoutingStreamedResp(
+                GatewayRoutingStreamedRespV1 {
+                    routings: vec![
+                        routing(1, vec![nested.clone(), subnet.clone()]),
+                        routing(2, vec![nested.clone()]),
+                        routing(1, vec![subnet.clone()]),
+                    ],
+                },
+            )),
+            ..Default::default()
+        });
+        let routings = response.deduped_routings().expect("routings");
+        assert_eq!(2, routings.len());
+        assert_eq!(routing(1, vec![subnet]), routings[0]);
+        assert_eq!(routing(2, vec![nested]), routings[1]);
+    }
+
+    #[test]
+    fn routing_ranges() {
+        let routing = Routing {
+            oui: 1,
+            subnets: vec![vec![0, 1, 240, 127, 255, 240], vec![0, 0, 0, 127, 255, 254]],
+            ..Default::default()
+        };
+        let netids = [NetId::from(0xC00035), NetId::from(0xE00001)];
+        let ranges = routing_devaddr_ranges(&routing, &netids);
+        let expected: Vec<(lorawan::subnet::DevAddr, u32)> = vec![
+            (netids[0].to_devaddr(992), 32),
+            (netids[1].to_devaddr(0), 32),
+            (netids[0].to_devaddr(0), 8),
+        ];
+        assert_eq!(
+            expected,
+            ranges
+                .iter()
+                .map(|range| (range.start, range.len()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn config_map_missing_keys() {
+        let var = |name: &str| BlockchainVarV1 {
+            name: name.to_string(),
+            r#type: "int".to_string(),
+            value: b"1".to_vec(),
+        };
+        let keys: Vec<String> = ["a", "b", "c"].iter().map(|k| k.to_string()).collect();
+
+        let map = config_map(&keys, vec![var("a"), var("b"), var("c")]).expect("config map");
+        assert_eq!(3, map.len());
+        assert_eq!("b", map["b"].name);
+
+        match config_map(&keys, vec![var("a"), var("c")]) {
+            Err(Error::Gateway(GatewayError::MissingKeys { keys })) => {
+                assert_eq!(vec!["b".to_string()], keys)
+            }
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    #[test]
+    fn retryable_errors() {
+        let retryable = |status: tonic::Status| RetryPolicy::is_retryable(&Error::from(status));
+        assert!(retryable(tonic::Status::unavailable("down")));
+        assert!(retryable(tonic::Status::deadline_exceeded("slow")));
+        assert!(!retryable(tonic::Status::invalid_argument("bad")));
+        assert!(!RetryPolicy::is_retryable(&GatewayError::empty_response(
+            "config"
+        )));
+    }
+
+    #[tokio::test]
+    async fn retry_budget() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let budget = RetryBudget::new(2, Duration::from_secs(3600));
+        let logger = slog_scope::logger();
+        let attempts = AtomicUsize::new(0);
+        let unavailable = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(tonic::Status::unavailable("down").into())
+        };
+
+        assert!(policy
+            .retry(&logger, Some(&budget), unavailable)
+            .await
+            .is_err());
+        assert_eq!(3, attempts.swap(0, Ordering::SeqCst));
+        // The budget is spent, so the next request fails fast
+        assert!(policy
+            .retry(&logger, Some(&budget), unavailable)
+            .await
+            .is_err());
+        assert_eq!(1, attempts.swap(0, Ordering::SeqCst));
+
+        assert!(policy.retry(&logger, None, unavailable).await.is_err());
+        assert_eq!(
+            policy.max_attempts as usize,
+            attempts.load(Ordering::SeqCst)
+        );
+    }
+
+    #[test]
+    fn monotonic_height() {
+        let mut height_check = HeightCheck::new(2);
+        for height in [10, 11, 11, 9, 12] {
+            height_check.check(height).expect("height within tolerance");
+        }
+        assert!(matches!(
+            height_check.check(9),
+            Err(Error::Gateway(GatewayError::HeightRegression {
+                last: 12,
+                height: 9
+            }))
+        ));
+        // The rejected height does not lower the bar
+        assert!(height_check.check(8).is_err());
+        height_check.check(13).expect("height advanced");
+    }
+
+    #[test]
+    fn sc_follow_events() {
+        let resp = |close_state: CloseState| GatewayScFollowStreamedRespV1 {
+            sc_id: vec![1],
+            close_state: close_state as i32,
+            ..Default::default()
+        };
+        let event = |close_state| ScFollowEvent::from(resp(close_state));
+        assert_eq!(
+            ScFollowEvent::Closable(resp(CloseState::Closable)),
+            event(CloseState::Closable)
+        );
+        assert_eq!(
+            ScFollowEvent::Closing(resp(CloseState::Closing)),
+            event(CloseState::Closing)
+        );
+        assert_eq!(
+            ScFollowEvent::Closed(resp(CloseState::Closed)),
+            event(CloseState::Closed)
+        );
+        assert_eq!(
+            ScFollowEvent::Dispute(resp(CloseState::Dispute)),
+            event(CloseState::Dispute)
+        );
+        assert_eq!(&[1], event(CloseState::Closed).sc_id());
+
+        let unknown = GatewayScFollowStreamedRespV1 {
+            close_state: 99,
+            ..resp(CloseState::Closable)
+        };
+        assert_eq!(
+            ScFollowEvent::Unknown(99, unknown.clone()),
+            ScFollowEvent::from(unknown)
+        );
+    }
+
+    #[tokio::test]
+    async fn replayed_follows() {
+        let follow = |id: u8| GatewayScFollowReqV1 {
+            sc_id: vec![id],
+            sc_owner: vec![1],
+        };
+        let (tx, mut rx) = mpsc::channel(3);
+        replay_follows(&tx, &[follow(1), follow(2)])
+            .await
+            .expect("replay");
+        assert_eq!(Some(follow(1)), rx.recv().await);
+        assert_eq!(Some(follow(2)), rx.recv().await);
+    }
+
+    #[tokio::test]
+    async fn follows_beyond_buffer() {
+        let follows: Vec<GatewayScFollowReqV1> = (0..10)
+            .map(|id| GatewayScFollowReqV1 {
+                sc_id: vec![id],
+                sc_owner: vec![1],
+            })
+            .collect();
+        let (tx, mut rx) = mpsc::channel(2);
+        let consumer = tokio::spawn(async move {
+            let mut received = vec![];
+            while let Some(follow) = rx.recv().await {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                received.push(follow);
+            }
+            received
+        });
+        replay_follows(&tx, &follows).await.expect("replay");
+        drop(tx);
+        assert_eq!(follows, consumer.await.expect("consumer"));
+    }
+
+    #[tokio::test]
+    async fn buffered_streaming() {
+        use helium_crypto::{KeyTag, KeyType, Network};
+        let keypair = Arc::new(Keypair::generate(
+            KeyTag {
+                network: Network::MainNet,
+                key_type: KeyType::Ed25519,
+            },
+            &mut OsRng,
+        ));
+        let mut responses = vec![];
+        for height in 0..4 {
+            let mut response = GatewayRespV1 {
+                height,
+                ..Default::default()
+            };
+            response.signature = sign_encoded(&response, keypair.clone())
+                .await
+                .expect("signature");
+            responses.push(response);
+        }
+        let verifier = keypair.public_key().clone();
+        let logger = slog_scope::logger();
+        let verified = Arc::new(AtomicUsize::new(0));
+        let stream = futures::stream::iter(responses).map({
+            let verified = verified.clone();
+            move |response| {
+                verify_response(&logger, &response, &verifier)?;
+                verified.fetch_add(1, Ordering::SeqCst);
+                Ok(Response(response))
+            }
+        });
+        let mut buffered = read_ahead(stream, 4);
+
+        // Nothing is consumed, yet everything received gets verified
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while verified.load(Ordering::SeqCst) < 4 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("verified ahead of the consumer");
+        assert_eq!(0, buffered.message_count());
+        assert_eq!(None, buffered.resume_position());
+
+        for height in 0..4 {
+            let response = buffered.message().await.expect("message").expect("open");
+            assert_eq!(height, response.height());
+        }
+        assert_eq!(4, buffered.message_count());
+        assert_eq!(Some(&ResumePosition::Height(3)), buffered.resume_position());
+        assert!(buffered.message().await.expect("end").is_none());
+    }
+
+    #[test]
+    fn unexpected_message() {
+        let response = Response(GatewayRespV1::default());
+        assert!(matches!(
+            response.routings(),
+            Err(Error::Gateway(GatewayError::UnexpectedMessage {
+                expected: "routing",
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn pool_distribution() {
+        let uris = [
+            keyed_uri("http://127.0.0.1:8080"),
+            keyed_uri("http://127.0.0.1:8081"),
+            keyed_uri("http://127.0.0.1:8082"),
+        ];
+        let count = |leases: &[PoolLease], uri: &KeyedUri| {
+            leases
+                .iter()
+                .filter(|lease| lease.service.uri.uri == uri.uri)
+                .count()
+        };
+
+        let pool = GatewayPool::new(&uris, 3, PoolStrategy::RoundRobin, 2).expect("pool");
+        let leases: Vec<PoolLease> = (0..10).map(|_| pool.checkout()).collect();
+        assert_eq!(4, count(&leases, &uris[0]));
+        assert_eq!(3, count(&leases, &uris[1]));
+        assert_eq!(3, count(&leases, &uris[2]));
+
+        let pool = GatewayPool::new(&uris, 3, PoolStrategy::LeastOutstanding, 2).expect("pool");
+        let mut leases: Vec<PoolLease> = (0..9).map(|_| pool.checkout()).collect();
+        // Finishing calls on the second service makes it the least busy
+        leases.retain(|lease| lease.service.uri.uri != uris[1].uri);
+        leases.push(pool.checkout());
+        assert_eq!(3, count(&leases, &uris[0]));
+        assert_eq!(1, count(&leases, &uris[1]));
+        assert_eq!(3, count(&leases, &uris[2]));
+    }
+
+    #[tokio::test]
+    async fn pool_replacement() {
+        let uris = [
+            keyed_uri("http://127.0.0.1:8080"),
+            keyed_uri("http://127.0.0.1:8081"),
+        ];
+        let pool = GatewayPool::new(&uris, 1, PoolStrategy::RoundRobin, 2).expect("pool");
+        let unavailable: Result<()> = Err(tonic::Status::unavailable("down").into());
+
+        let lease = pool.checkout();
+        pool.checkin(&lease, &unavailable);
+        pool.checkin(&lease, &Ok(()));
+        pool.checkin(&lease, &unavailable);
+        assert_eq!(uris[0].uri, pool.checkout().service.uri.uri);
+
+        pool.checkin(&lease, &unavailable);
+        assert_eq!(uris[1].uri, pool.checkout().service.uri.uri);
+        assert_eq!(1, pool.size());
+    }
+
+    #[tokio::test]
+    async fn new_signed() {
+        use helium_crypto::{KeyTag, KeyType, Network, Verify};
+        let keypair = Arc::new(Keypair::generate(
+            KeyTag {
+                network: Network::MainNet,
+                key_type: KeyType::Ed25519,
+            },
+            &mut OsRng,
+        ));
+        let msg = GatewayConfigReqV1 { keys: vec![] };
+
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080")).expect("service");
+        let request = service.request(msg.clone()).await.expect("request");
+        assert!(request.metadata().get_bin(SIGNATURE_HEADER).is_none());
+
+        let service =
+            GatewayService::new_signed(keyed_uri("http://127.0.0.1:8080"), keypair.clone())
+                .expect("service");
+        let request = service.request(msg.clone()).await.expect("request");
+        let header = |key| {
+            request
+                .metadata()
+                .get_bin(key)
+                .expect("header")
+                .to_bytes()
+                .expect("header bytes")
+        };
+        assert_eq!(
+            keypair.public_key().to_vec(),
+            header(PUBKEY_HEADER).to_vec()
+        );
+        keypair
+            .public_key()
+            .verify(&msg.encode_to_vec(), &header(SIGNATURE_HEADER))
+            .expect("valid signature");
+    }
+
+    #[tokio::test]
+    async fn shared_channel() {
+        // Nothing listens here, so the call fails, but it does go out over
+        // the shared channel
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:1")).expect("service");
+        let mut client = services::gateway::Client::new(service.channel());
+        let status = client
+            .config(GatewayConfigReqV1 { keys: vec![] })
+            .await
+            .expect_err("no validator");
+        assert!(Error::from(status).is_transient());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn new_uds() {
+        let path = std::env::temp_dir().join(format!("gateway-rs-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).expect("listener");
+        let pubkey = keyed_uri("http://127.0.0.1:8080").pubkey;
+        let mut service = GatewayService::new_uds(&path, pubkey).expect("service");
+        assert_eq!(
+            format!("unix://localhost{}", path.display()),
+            service.uri.uri.to_string()
+        );
+
+        // The listener doesn't speak http/2, so the request fails after the
+        // connection to the socket is accepted
+        let accept = async { listener.accept().await.map(|_| ()) };
+        let (accepted, height) = tokio::join!(accept, service.height());
+        accepted.expect("connection over the socket");
+        assert!(height.is_err());
+        let _ = std::fs::remove_file(&path);
+
+        assert!(GatewayService::new_uds("relative.sock", service.uri.pubkey.clone()).is_err());
+    }
+
+    #[tokio::test]
+    async fn max_concurrency() {
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080"))
+            .expect("service")
+            .with_max_concurrency(4);
+        let mut permits = Vec::new();
+        for _ in 0..4 {
+            permits.push(service.permit().await.expect("permit"));
+        }
+        let clone = service.clone();
+        let fifth = tokio::time::timeout(Duration::from_millis(50), clone.permit()).await;
+        assert!(fifth.is_err());
+
+        permits.pop();
+        let fifth = tokio::time::timeout(Duration::from_millis(50), clone.permit()).await;
+        assert!(fifth.expect("permit after one completes").is_some());
+    }
+
+    #[tokio::test]
+    async fn rotating_signer() {
+        use helium_crypto::{KeyTag, KeyType, Network, Verify};
+        let new_keypair = || {
+            Arc::new(Keypair::generate(
+                KeyTag {
+                    network: Network::MainNet,
+                    key_type: KeyType::Ed25519,
+                },
+                &mut OsRng,
+            ))
+        };
+        let current = Arc::new(Mutex::new(new_keypair()));
+        let service = GatewayService::new_signed_with(keyed_uri("http://127.0.0.1:8080"), {
+            let current = current.clone();
+            move || current.lock().expect("current key").clone()
+        })
+        .expect("service");
+        let msg = GatewayConfigReqV1 { keys: vec![] };
+        let signed_by = |request: &tonic::Request<GatewayConfigReqV1>| {
+            let header = |key| {
+                request
+                    .metadata()
+                    .get_bin(key)
+                    .expect("header")
+                    .to_bytes()
+                    .expect("header bytes")
+            };
+            (header(PUBKEY_HEADER).to_vec(), header(SIGNATURE_HEADER))
+        };
+
+        for _ in 0..2 {
+            let keypair = current.lock().expect("current key").clone();
+            let request = service.request(msg.clone()).await.expect("request");
+            let (pubkey, signature) = signed_by(&request);
+            assert_eq!(keypair.public_key().to_vec(), pubkey);
+            keypair
+                .public_key()
+                .verify(&msg.encode_to_vec(), &signature)
+                .expect("signed with the current key");
+            // Rotate the key for the next request
+            *current.lock().expect("current key") = new_keypair();
+        }
+    }
+
+    #[tokio::test]
+    async fn with_metadata() {
+        use helium_crypto::{KeyTag, KeyType, Network};
+        let keypair = Arc::new(Keypair::generate(
+            KeyTag {
+                network: Network::MainNet,
+                key_type: KeyType::Ed25519,
+            },
+            &mut OsRng,
+        ));
+        let headers = HashMap::from([("x-gateway-region".to_string(), "US915".to_string())]);
+        let service = GatewayService::new_signed(keyed_uri("http://127.0.0.1:8080"), keypair)
+            .expect("service")
+            .with_metadata(headers)
+            .expect("metadata");
+        let request = service
+            .request(GatewayConfigReqV1 { keys: vec![] })
+            .await
+            .expect("request");
+        assert_eq!(
+            "US915",
+            request
+                .metadata()
+                .get("x-gateway-region")
+                .expect("header")
+                .to_str()
+                .expect("ascii header")
+        );
+        assert!(request.metadata().get_bin(SIGNATURE_HEADER).is_some());
+
+        let invalid = HashMap::from([("x-gateway-region".to_string(), "US\n915".to_string())]);
+        assert!(service.clone().with_metadata(invalid).is_err());
+        let invalid = HashMap::from([("x gateway".to_string(), "US915".to_string())]);
+        assert!(service.clone().with_metadata(invalid).is_err());
+        // A valid header name, but a binary one
+        let invalid = HashMap::from([("x-gateway-bin".to_string(), "US915".to_string())]);
+        assert!(service.with_metadata(invalid).is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080")).expect("service");
+        let mut clone = service.clone();
+        let in_flight = service.drain.begin().expect("in flight request");
+
+        let mut shutdown = tokio::spawn(service.shutdown(Duration::from_secs(5)));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(clone.drain.begin().is_err());
+        assert!(matches!(
+            clone.height().await,
+            Err(Error::Service(ServiceError::Shutdown))
+        ));
+        let waiting = tokio::time::timeout(Duration::from_millis(20), &mut shutdown).await;
+        assert!(waiting.is_err());
+
+        // Shutdown completes as soon as the in flight request does
+        let start = Instant::now();
+        drop(in_flight);
+        shutdown.await.expect("shutdown");
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(clone.drain.begin().is_err());
+    }
+
+    #[tokio::test]
+    async fn shutdown_grace() {
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080")).expect("service");
+        let _in_flight = service.drain.begin().expect("in flight request");
+        let drain = service.drain.clone();
+        service.shutdown(Duration::from_millis(20)).await;
+        assert_eq!(1, drain.outstanding());
+        assert!(drain.begin().is_err());
+    }
+
+    #[tokio::test]
+    async fn fastest_probe() {
+        let probe = |uri, millis| {
+            let service = GatewayService::new(keyed_uri(uri)).expect("service");
+            Ok((service, Duration::from_millis(millis)))
+        };
+        let service = fastest(vec![
+            probe("http://127.0.0.1:8080", 300),
+            Err(tonic::Status::unavailable("down").into()),
+            probe("http://127.0.0.1:8081", 20),
+        ])
+        .expect("fastest");
+        assert_eq!(keyed_uri("http://127.0.0.1:8081"), service.uri);
+        assert!(fastest(vec![Err(tonic::Status::unavailable("down").into())]).is_none());
+    }
+
+    #[test]
+    fn weighted_order() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let uris = [
+            keyed_uri("http://127.0.0.1:8080"),
+            keyed_uri("http://127.0.0.1:8081"),
+        ];
+        let heavy = |uri: &KeyedUri| if uri == &uris[0] { 9.0 } else { 1.0 };
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut heavy_first = 0;
+        for _ in 0..1000 {
+            let order = super::weighted_order(&uris, heavy, &mut rng);
+            assert_eq!(2, order.len());
+            if order[0] == uris[0] {
+                heavy_first += 1;
+            }
+        }
+        assert!((850..950).contains(&heavy_first), "{heavy_first}");
+
+        // All zero weights fall back to a uniform order
+        let mut zero_first = 0;
+        for _ in 0..1000 {
+            if super::weighted_order(&uris, |_| 0.0, &mut rng)[0] == uris[0] {
+                zero_first += 1;
+            }
+        }
+        assert!((400..600).contains(&zero_first), "{zero_first}");
+
+        let only_second = |uri: &KeyedUri| if uri == &uris[1] { 1.0 } else { 0.0 };
+        assert_eq!(
+            uris[1],
+            super::weighted_order(&uris, only_second, &mut rng)[0]
+        );
+    }
+
+    #[test]
+    fn grpc_code() {
+        let err = Error::from(tonic::Status::unavailable("validator down"));
+        assert_eq!(Some(tonic::Code::Unavailable), err.grpc_code());
+        assert_eq!(Some("validator down"), err.grpc_message());
+        let err = Error::from(tonic::Status::unauthenticated("unknown gateway"));
+        assert_eq!(Some(tonic::Code::Unauthenticated), err.grpc_code());
+        assert!(!err.is_transient());
+        assert_eq!(None, GatewayError::empty_response("config").grpc_code());
+    }
+
+    #[tokio::test]
+    async fn random_new() {
+        // Nothing listens on the tcpmux or compressnet ports, so connections
+        // are refused
+        let dead = [
+            keyed_uri("http://127.0.0.1:1"),
+            keyed_uri("http://127.0.0.1:2"),
+        ];
+        let start = Instant::now();
+        let err = GatewayService::random_new(&dead)
+            .await
+            .expect_err("no gateway responds");
+        assert!(err.is_transient());
+        assert!(start.elapsed() < Duration::from_secs(CONNECT_TIMEOUT));
+        assert!(GatewayService::random_new(&[]).await.is_err());
+
+        // The first gateway fails its probe, the second passes
+        let tried = Mutex::new(vec![]);
+        let probe = |service: GatewayService| {
+            tried.lock().expect("tried").push(service.uri.clone());
+            let first = service.uri == dead[0];
+            async move {
+                if first {
+                    Err(tonic::Status::unavailable("down").into())
+                } else {
+                    Ok(service)
+                }
+            }
+        };
+        let service = GatewayService::first_responding(dead.to_vec(), probe)
+            .await
+            .expect("second gateway");
+        assert_eq!(dead[1], service.uri);
+        assert_eq!(dead.to_vec(), *tried.lock().expect("tried"));
+
+        // The error is returned once every gateway was tried
+        tried.lock().expect("tried").clear();
+        let err = GatewayService::first_responding(dead.to_vec(), |service| {
+            tried.lock().expect("tried").push(service.uri.clone());
+            async { Err::<GatewayService, _>(tonic::Status::unavailable("down").into()) }
+        })
+        .await
+        .expect_err("no gateway passes");
+        assert_eq!(Some(tonic::Code::Unavailable), err.grpc_code());
+        assert_eq!(2, tried.lock().expect("tried").len());
+    }
+
+    #[tokio::test]
+    async fn ping_unreachable() {
+        let mut service = GatewayService::new(keyed_uri("http://127.0.0.1:1")).expect("service");
+        let err = service.ping().await.expect_err("nothing listening");
+        assert!(err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn with_timeouts() {
+        let service = GatewayService::with_timeouts(
+            keyed_uri("http://127.0.0.1:8080"),
+            Duration::from_secs(30),
+            Duration::from_secs(RPC_TIMEOUT),
+        )
+        .expect("service");
+        assert_eq!(Duration::from_secs(30), service.connect_timeout());
+        assert_eq!(Duration::from_secs(RPC_TIMEOUT), service.rpc_timeout());
+
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080")).expect("service");
+        assert_eq!(
+            Duration::from_secs(CONNECT_TIMEOUT),
+            service.connect_timeout()
+        );
+    }
+
+    #[tokio::test]
+    async fn with_keep_alive() {
+        let service = GatewayService::new(keyed_uri("http://127.0.0.1:8080")).expect("service");
+        assert_eq!(KeepAliveConfig::default(), service.keep_alive());
+
+        let keep_alive = KeepAliveConfig {
+            interval: Duration::from_secs(15),
+            timeout: Duration::from_secs(5),
+            while_idle: true,
+        };
+        let service = service.with_keep_alive(keep_alive);
+        assert_eq!(keep_alive, service.keep_alive());
+        assert_eq!(Duration::from_secs(RPC_TIMEOUT), service.rpc_timeout());
+    }
+
+    #[tokio::test]
+    async fn connect_unreachable() {
+        let start = Instant::now();
+        // Nothing listens on the tcpmux port, so the connection is refused
+        let result = GatewayService::connect(keyed_uri("http://127.0.0.1:1")).await;
+        assert!(result.is_err());
+        assert!(start.elapsed() < Duration::from_secs(CONNECT_TIMEOUT));
+    }
+
+    /// A validator answering every gateway request with the given status
+    #[derive(Clone)]
+    struct StatusServer(tonic::Code);
+
+    impl tonic::transport::NamedService for StatusServer {
+        const NAME: &'static str = "helium.gateway";
+    }
+
+    impl tower::Service<http::Request<tonic::transport::Body>> for StatusServer {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = futures::future::Ready<std::result::Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: http::Request<tonic::transport::Body>) -> Self::Future {
+            futures::future::ready(Ok(tonic::Status::new(self.0, "test").to_http()))
+        }
+    }
+
+    /// Serve a `StatusServer` on a local port, returning its uri
+    async fn serve_status(code: tonic::Code) -> Uri {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let port = listener.local_addr().expect("local addr").port();
+        let incoming = futures::stream::unfold(listener, |listener| async {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(StatusServer(code))
+                .serve_with_incoming(incoming),
+        );
+        format!("http://127.0.0.1:{port}").parse().expect("uri")
+    }
+
+    #[tokio::test]
+    async fn connection_state() {
+        // Nothing listens on the tcpmux port, so the connection is refused
+        let mut service = GatewayService::new(keyed_uri("http://127.0.0.1:1")).expect("service");
+        let mut watcher = service.watch_connection_state();
+        assert_eq!(ConnectionState::Idle, service.connection_state());
+
+        assert!(service.height().await.is_err());
+        assert_eq!(
+            ConnectionState::TransientFailure,
+            service.connection_state()
+        );
+        watcher.changed().await.expect("state change");
+        assert_eq!(ConnectionState::TransientFailure, *watcher.borrow());
+
+        // A validator that answers, if only with an error status, is
+        // connected
+        let mut service = GatewayService::new(KeyedUri {
+            uri: serve_status(tonic::Code::NotFound).await,
+            ..keyed_uri("http://127.0.0.1:8080")
+        })
+        .expect("service");
+        let mut watcher = service.watch_connection_state();
+        assert_eq!(ConnectionState::Idle, *watcher.borrow());
+        let watch = async {
+            let mut states = vec![];
+            for _ in 0..2 {
+                watcher.changed().await.expect("state change");
+                states.push(*watcher.borrow());
+            }
+            states
+        };
+        let (height, states) = tokio::join!(service.height(), watch);
+        assert_eq!(
+            Some(tonic::Code::NotFound),
+            height.expect_err("status").grpc_code()
+        );
+        assert_eq!(
+            vec![ConnectionState::Connecting, ConnectionState::Ready],
+            states
+        );
+        assert_eq!(ConnectionState::Ready, service.connection_state());
+
+        // Clones share the state, reconnected services start over
+        assert_eq!(ConnectionState::Ready, service.clone().connection_state());
+        assert_eq!(
+            ConnectionState::Idle,
+            service.reconnect().connection_state()
+        );
+    }
+
+    #[tokio::test]
+    async fn call_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let port = listener.local_addr().expect("local addr").port();
+        let uri: Uri = format!("http://127.0.0.1:{port}").parse().expect("uri");
+        let mut service = GatewayService::with_timeouts(
+            KeyedUri {
+                uri,
+                ..keyed_uri("http://127.0.0.1:8080")
+            },
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+        )
+        .expect("service");
+
+        // The connection is held open without a response, so only the per
+        // call timeout ends the request
+        let start = Instant::now();
+        let accept = async { listener.accept().await.map(|(stream, _)| stream) };
+        let (stream, height) = tokio::join!(
+            accept,
+            service.height_with_timeout(Duration::from_millis(100))
+        );
+        let _stream = stream.expect("connection");
+        let err = height.expect_err("per call timeout");
+        assert_eq!(Some(tonic::Code::DeadlineExceeded), err.grpc_code());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(Duration::from_secs(30), service.rpc_timeout());
+        assert_eq!(None, service.call_timeout);
+    }
+
+    #[tokio::test]
+    async fn reconnect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let port = listener.local_addr().expect("local addr").port();
+        let uri: Uri = format!("http://localhost:{port}").parse().expect("uri");
+        let mut service = GatewayService::with_timeouts(
+            KeyedUri {
+                uri,
+                ..keyed_uri("http://127.0.0.1:8080")
+            },
+            Duration::from_secs(1),
+            Duration::from_millis(200),
+        )
+        .expect("service");
+
+        // The first connection is held open, so the service would keep
+        // using it. The reconnected service resolves and connects again.
+        let accept = async { listener.accept().await.map(|(stream, _)| stream) };
+        let (first, height) = tokio::join!(accept, service.height());
+        let _first = first.expect("first connection");
+        assert!(height.is_err());
+        let mut reconnected = service.reconnect();
+        assert_eq!(service.uri, reconnected.uri);
+        let accept = async { listener.accept().await.map(|_| ()) };
+        let (second, height) = tokio::join!(accept, reconnected.height());
+        second.expect("second connection");
+        assert!(height.is_err());
+    }
+
+    #[tokio::test]
+    async fn call_timeout_longer() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener");
+        let port = listener.local_addr().expect("local addr").port();
+        let uri: Uri = format!("http://localhost:{port}").parse().expect("uri");
+        let mut service = GatewayService::with_timeouts(
+            KeyedUri {
+                uri,
+                ..keyed_uri("http://127.0.0.1:8080")
+            },
+            Duration::from_secs(1),
+            Duration::from_millis(100),
+        )
+        .expect("service");
+
+        // The connection is accepted and held open without a response, so
+        // every call runs into its timeout
+        let accept = async { listener.accept().await.map(|(stream, _)| stream) };
+        let start = tokio::time::Instant::now();
+        let (held, height) = tokio::join!(accept, service.height());
+        let _held = held.expect("connection");
+        assert_eq!(
+            Some(tonic::Code::DeadlineExceeded),
+            height.expect_err("default timeout").grpc_code()
+        );
+        assert!(start.elapsed() < Duration::from_millis(400));
+
+        let start = tokio::time::Instant::now();
+        let height = service
+            .height_with_timeout(Duration::from_millis(500))
+            .await;
+        assert_eq!(
+            Some(tonic::Code::DeadlineExceeded),
+            height.expect_err("per-call timeout").grpc_code()
+        );
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn ipv6_literal() {
+        let listener = match tokio::net::TcpListener::bind("[::1]:0").await {
+            Ok(listener) => listener,
+            // No IPv6 loopback on this host
+            Err(_) => return,
+        };
+        let port = listener.local_addr().expect("local addr").port();
+        let uri: Uri = format!("http://[::1]:{port}").parse().expect("uri");
+        assert_eq!(Some("[::1]"), uri.host());
+        let mut service = GatewayService::new(KeyedUri {
+            uri,
+            ..keyed_uri("http://127.0.0.1:8080")
+        })
+        .expect("service");
+
+        // The listener doesn't speak http/2, so the request fails after the
+        // connection is accepted
+        let accept = async { listener.accept().await.map(|_| ()) };
+        let (accepted, height) = tokio::join!(accept, service.height());
+        accepted.expect("connection over ipv6");
+        assert!(height.is_err());
+    }
+
+    #[cfg(feature = "tls")]
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBkTCCATegAwIBAgIUI6r2+6QLwcd6LcI2drz0XBhiu+gwCgYIKoZIzj0EAwIw
+HTEbMBkGA1UEAwwSZ2F0ZXdheS1ycyB0ZXN0IENBMCAXDTI2MTAxNTA4MDczOFoY
+DzIxMjYwOTIxMDgwNzM4WjAdMRswGQYDVQQDDBJnYXRld2F5LXJzIHRlc3QgQ0Ew
+WTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAQyMSZCAHcmuxWUydb9rhGNXPJP6X/p
+9AtZVvqQ3JuhbK4DeAs0hyZna+vBtQz6NrXw3gpAchADus6Uic7q5/aQo1MwUTAd
+BgNVHQ4EFgQUf5jR+tqPnOPInfvT0+uJ/P4cXi0wHwYDVR0jBBgwFoAUf5jR+tqP
+nOPInfvT0+uJ/P4cXi0wDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBF
+AiBewDDu5LJJl88QeTSheAMOgzfN83ryf7hdc2KKLhxXowIhAOmkyftGgzcU+XpY
+QM3LlrbwFEJsNCxKkhIGhepQ3NhB
+-----END CERTIFICATE-----
+";
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn new_tls() {
+        use tonic::transport::Certificate;
+        let tls_config = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(TEST_CA))
+            .domain_name("localhost");
+        GatewayService::new_tls(keyed_uri("https://localhost:8080"), tls_config)
+            .expect("tls service");
+        GatewayService::new(keyed_uri("https://localhost:8080")).expect("https service");
     }
 }
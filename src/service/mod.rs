@@ -1,4 +1,5 @@
 pub const CONNECT_TIMEOUT: u64 = 10;
+pub const RPC_TIMEOUT: u64 = 10;
 
 pub mod gateway;
 pub mod router;
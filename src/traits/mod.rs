@@ -5,6 +5,7 @@ mod txn_envelope;
 mod txn_fee;
 
 pub use self::base64::Base64;
+pub(crate) use msg_sign::sign_encoded;
 pub use msg_sign::MsgSign;
 pub use msg_verify::MsgVerify;
 pub use txn_envelope::TxnEnvelope;
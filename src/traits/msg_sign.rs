@@ -21,16 +21,23 @@ macro_rules! impl_msg_sign {
             async fn sign(&self, keypair: Arc<Keypair>) -> Result<Vec<u8>> {
                 let mut txn = self.clone();
                 $(txn.$sig = vec![];)+
-                let buf = txn.encode_to_vec();
-                let join_handle: tokio::task::JoinHandle<Result<Vec<u8>>> = tokio::task::spawn_blocking(move ||  {
-                    keypair.sign(&buf).map_err(Error::from)
-                });
-                join_handle.map_err(|err| helium_crypto::Error::from(signature::Error::from_source(err))).await?
+                sign_encoded(&txn, keypair).await
             }
         }
     };
 }
 
+/// Sign the encoding of a message, for messages without a signature field of
+/// their own
+pub(crate) async fn sign_encoded<T: Message>(msg: &T, keypair: Arc<Keypair>) -> Result<Vec<u8>> {
+    let buf = msg.encode_to_vec();
+    let join_handle: tokio::task::JoinHandle<Result<Vec<u8>>> =
+        tokio::task::spawn_blocking(move || keypair.sign(&buf).map_err(Error::from));
+    join_handle
+        .map_err(|err| helium_crypto::Error::from(signature::Error::from_source(err)))
+        .await?
+}
+
 impl_msg_sign!(GatewayRegionParamsUpdateReqV1, signature);
 impl_msg_sign!(BlockchainStateChannelPacketV1, signature);
 impl_msg_sign!(BlockchainStateChannelOfferV1, signature);